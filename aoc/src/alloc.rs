@@ -0,0 +1,160 @@
+//! Allocation accounting for the days.
+//!
+//! A day opts in by depending on this crate and forwarding its `alloc-stats` feature:
+//!
+//! ```toml
+//! [dependencies]
+//! aoc = { path = "../aoc" }
+//!
+//! [features]
+//! alloc-stats = ["aoc/alloc-stats"]
+//! ```
+//!
+//! and wrapping each phase in [`measure`]. Without the feature the counting allocator is not installed
+//! and [`measure`] just runs the closure.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Wraps the system allocator and counts every allocation it hands out
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record_allocation(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record_allocation(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+            record_allocation(new_size);
+        }
+        new_ptr
+    }
+}
+
+fn record_allocation(size: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    BYTES.fetch_add(size, Ordering::Relaxed);
+    let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(current, Ordering::Relaxed);
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AllocStats {
+    /// Number of allocations, including reallocations
+    pub allocations: usize,
+    /// Total bytes requested over all allocations
+    pub bytes: usize,
+    /// Largest amount of heap in use at any point
+    pub peak: usize,
+}
+
+impl AllocStats {
+    /// Formats the stats the way [`AllocStats::parse`] reads them back, e.g. `Alloc[parse]: allocations=3 bytes=96 peak=64`
+    pub fn to_line(&self, label: &str) -> String {
+        format!("Alloc[{}]: allocations={} bytes={} peak={}", label, self.allocations, self.bytes, self.peak)
+    }
+
+    /// Reads a line written by [`AllocStats::to_line`], returning the label and the stats
+    pub fn parse(line: &str) -> Option<(String, AllocStats)> {
+        let rest = line.trim().strip_prefix("Alloc[")?;
+        let (label, rest) = rest.split_once("]:")?;
+
+        let mut stats = AllocStats { allocations: 0, bytes: 0, peak: 0 };
+        for field in rest.split_ascii_whitespace() {
+            let (name, value) = field.split_once('=')?;
+            let value = value.parse::<usize>().ok()?;
+            match name {
+                "allocations" => stats.allocations = value,
+                "bytes" => stats.bytes = value,
+                "peak" => stats.peak = value,
+                _ => return None,
+            }
+        }
+
+        Some((label.to_string(), stats))
+    }
+}
+
+/// Returns true when the counting allocator is installed
+pub fn is_enabled() -> bool {
+    cfg!(feature = "alloc-stats")
+}
+
+/// Runs `f` and, when the counting allocator is installed, prints what it allocated to stderr.
+/// Stderr keeps the stats out of the way of the answer, which the days print to stdout.
+pub fn measure<T>(label: &str, f: impl FnOnce() -> T) -> T {
+    if !is_enabled() {
+        return f();
+    }
+
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = BYTES.load(Ordering::Relaxed);
+    PEAK.store(CURRENT.load(Ordering::Relaxed), Ordering::Relaxed);
+
+    let result = f();
+
+    let stats = AllocStats {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        bytes: BYTES.load(Ordering::Relaxed) - bytes,
+        peak: PEAK.load(Ordering::Relaxed),
+    };
+    eprintln!("{}", stats.to_line(label));
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_line_round_trips() {
+        let stats = AllocStats { allocations: 3, bytes: 96, peak: 64 };
+        let line = stats.to_line("parse");
+        assert_eq!(line, "Alloc[parse]: allocations=3 bytes=96 peak=64");
+        assert_eq!(AllocStats::parse(&line), Some((String::from("parse"), stats)));
+    }
+
+    #[test]
+    fn parse_rejects_other_lines() {
+        assert_eq!(AllocStats::parse("Answer: 42"), None);
+        assert_eq!(AllocStats::parse("Alloc[part]: allocations=x"), None);
+        assert_eq!(AllocStats::parse("Alloc[part]: colour=3"), None);
+    }
+
+    #[test]
+    fn measure_returns_result() {
+        assert_eq!(measure("test", || vec![1, 2, 3].len()), 3);
+    }
+}
@@ -0,0 +1,149 @@
+use std::{fmt::Write, time::Duration};
+
+use aoc::alloc::AllocStats;
+
+use crate::runner::{self, Day, Outcome};
+
+pub struct BenchResult {
+    pub times: Vec<Duration>,
+    /// Allocation stats per phase, only present for days built with `alloc-stats`
+    pub alloc: Vec<(String, AllocStats)>,
+}
+
+pub struct DayBench {
+    pub day: Day,
+    pub result: Result<BenchResult, String>,
+}
+
+/// Runs a day `runs` times for timing. Days that support it are then run once more with the
+/// counting allocator, so the allocator's overhead never shows up in the timings.
+pub fn bench_day(day: Day, runs: usize) -> DayBench {
+    let result = get_bench_result(&day, runs);
+    DayBench { day, result }
+}
+
+fn get_bench_result(day: &Day, runs: usize) -> Result<BenchResult, String> {
+    if !day.path.join("input.txt").exists() {
+        return Err(String::from("No input.txt found"));
+    }
+
+    let binary = runner::build_day(day, false)?;
+    let mut times = Vec::new();
+    for _ in 0..runs {
+        match runner::run_binary(day, &binary) {
+            Outcome::Ran { success: true, elapsed, .. } => times.push(elapsed),
+            Outcome::Ran { stderr, .. } => return Err(stderr),
+            Outcome::BuildFailed(e) => return Err(e),
+            Outcome::MissingInput => return Err(String::from("No input.txt found")),
+        }
+    }
+
+    let mut alloc = Vec::new();
+    if runner::has_alloc_stats(day) {
+        let binary = runner::build_day(day, true)?;
+        if let Outcome::Ran { stderr, .. } = runner::run_binary(day, &binary) {
+            alloc = stderr.lines().filter_map(AllocStats::parse).collect();
+        }
+    }
+
+    Ok(BenchResult { times, alloc })
+}
+
+/// Renders the results as a plain text table, one row per allocation phase
+pub fn render(benches: &[DayBench]) -> String {
+    let mut table = String::new();
+    writeln!(table, "{:<8} {:>5} {:>12} {:>12}   {:<10} {:>12} {:>12} {:>12}",
+        "day", "runs", "min", "mean", "phase", "allocations", "bytes", "peak").unwrap();
+
+    for bench in benches {
+        let result = match &bench.result {
+            Ok(result) => result,
+            Err(e) => {
+                let first_line = e.lines().next().unwrap_or("");
+                writeln!(table, "{:<8} error: {}", bench.day.name, first_line).unwrap();
+                continue;
+            },
+        };
+
+        let min = result.times.iter().min().copied().unwrap_or_default();
+        let mean = result.times.iter().sum::<Duration>() / result.times.len().max(1) as u32;
+        let times = format!("{:<8} {:>5} {:>12} {:>12}",
+            bench.day.name, result.times.len(), format!("{:.2?}", min), format!("{:.2?}", mean));
+
+        if result.alloc.is_empty() {
+            writeln!(table, "{}   -", times).unwrap();
+            continue;
+        }
+
+        for (i, (phase, stats)) in result.alloc.iter().enumerate() {
+            let prefix = if i == 0 { times.clone() } else { " ".repeat(times.len()) };
+            writeln!(table, "{}   {:<10} {:>12} {:>12} {:>12}",
+                prefix, phase, stats.allocations, format_bytes(stats.bytes), format_bytes(stats.peak)).unwrap();
+        }
+    }
+
+    table
+}
+
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn day(name: &str) -> Day {
+        Day { name: String::from(name), number: 5, part: 'b', path: PathBuf::new() }
+    }
+
+    #[test]
+    fn format_bytes_works() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+    }
+
+    #[test]
+    fn render_lists_every_phase() {
+        let benches = vec![
+            DayBench {
+                day: day("day5b"),
+                result: Ok(BenchResult {
+                    times: vec![Duration::from_millis(2), Duration::from_millis(4)],
+                    alloc: vec![
+                        (String::from("parse"), AllocStats { allocations: 10, bytes: 2048, peak: 1024 }),
+                        (String::from("part"), AllocStats { allocations: 1, bytes: 8, peak: 1032 }),
+                    ],
+                }),
+            },
+            DayBench { day: day("day5a"), result: Err(String::from("No input.txt found")) },
+        ];
+
+        let table = render(&benches);
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].starts_with("day5b"));
+        assert!(lines[1].contains("2.00ms") && lines[1].contains("3.00ms"));
+        assert!(lines[1].contains("parse") && lines[1].contains("2.0 KiB"));
+        assert!(lines[2].trim_start().starts_with("part"));
+        assert_eq!(lines[3], "day5a    error: No input.txt found");
+    }
+}
@@ -0,0 +1,220 @@
+//! Per-line breakdown of days whose answer is a sum over lines.
+//!
+//! Each row holds the line number, the raw line, what the line added to the answer and any intermediate values
+//! the day wants to show. Days write it with `--breakdown <file>`: a `.json` file gets a JSON array of objects,
+//! anything else gets CSV with a header row.
+
+use std::{fmt::Display, fs, io};
+
+/// A single cell. Numbers are kept apart from text so they are written unquoted in JSON.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(String),
+    Text(String),
+    Bool(bool),
+}
+
+impl Value {
+    pub fn number(number: impl Display) -> Value {
+        Value::Number(number.to_string())
+    }
+
+    pub fn text(text: impl Into<String>) -> Value {
+        Value::Text(text.into())
+    }
+}
+
+struct Row {
+    line: usize,
+    raw: String,
+    contribution: Value,
+    fields: Vec<Value>,
+}
+
+pub struct Breakdown {
+    fields: Vec<String>,
+    rows: Vec<Row>,
+}
+
+impl Breakdown {
+    /// `fields` names the intermediate values every row has, after the line, raw line and contribution
+    pub fn new(fields: &[&str]) -> Breakdown {
+        Breakdown {
+            fields: fields.iter().map(|field| field.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Adds a row. `line` is 1-based, `raw` is the line as it appeared in the input.
+    pub fn add(&mut self, line: usize, raw: &[u8], contribution: impl Display, fields: Vec<Value>) {
+        self.add_value(line, raw, Value::number(contribution), fields);
+    }
+
+    /// Like [`Breakdown::add`], for a contribution that isn't always a number
+    pub fn add_value(&mut self, line: usize, raw: &[u8], contribution: Value, fields: Vec<Value>) {
+        assert_eq!(fields.len(), self.fields.len(), "Every row should have a value for every field");
+
+        self.rows.push(Row {
+            line,
+            raw: String::from_utf8_lossy(raw).to_string(),
+            contribution,
+            fields,
+        });
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("line,raw,contribution");
+        for field in &self.fields {
+            csv.push(',');
+            csv.push_str(&escape_csv(field));
+        }
+        csv.push('\n');
+
+        for row in &self.rows {
+            csv.push_str(&row.line.to_string());
+            csv.push(',');
+            csv.push_str(&escape_csv(&row.raw));
+            for value in std::iter::once(&row.contribution).chain(&row.fields) {
+                csv.push(',');
+                match value {
+                    Value::Number(number) => csv.push_str(number),
+                    Value::Text(text) => csv.push_str(&escape_csv(text)),
+                    Value::Bool(b) => csv.push_str(&b.to_string()),
+                }
+            }
+            csv.push('\n');
+        }
+
+        csv
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("[\n");
+        for (i, row) in self.rows.iter().enumerate() {
+            json.push_str(&format!("  {{\"line\": {}, \"raw\": {}, \"contribution\": {}",
+                row.line, escape_json(&row.raw), json_value(&row.contribution)));
+            for (field, value) in self.fields.iter().zip(&row.fields) {
+                json.push_str(&format!(", {}: {}", escape_json(field), json_value(value)));
+            }
+            json.push('}');
+            if i + 1 < self.rows.len() {
+                json.push(',');
+            }
+            json.push('\n');
+        }
+        json.push_str("]\n");
+        json
+    }
+
+    /// Writes JSON if `path` ends in `.json`, CSV otherwise
+    pub fn write(&self, path: &str) -> io::Result<()> {
+        if path.ends_with(".json") {
+            fs::write(path, self.to_json())
+        } else {
+            fs::write(path, self.to_csv())
+        }
+    }
+}
+
+/// Returns the file following `--breakdown`, if it was passed
+pub fn path_from_args(args: &[String]) -> Option<&str> {
+    let position = args.iter().position(|arg| arg == "--breakdown")?;
+    args.get(position + 1).map(|path| path.as_str())
+}
+
+/// An error if `--breakdown` is passed with `--stream`: a breakdown keeps a row for every line, which is what
+/// streaming avoids
+pub fn check_not_streaming(args: &[String]) -> Result<(), &'static str> {
+    if path_from_args(args).is_some() && args.iter().any(|arg| arg == "--stream") {
+        return Err("--breakdown needs the whole input, so it can't be combined with --stream");
+    }
+    Ok(())
+}
+
+fn json_value(value: &Value) -> String {
+    match value {
+        Value::Number(number) => number.clone(),
+        Value::Text(text) => escape_json(text),
+        Value::Bool(b) => b.to_string(),
+    }
+}
+
+/// Quotes a CSV cell when it contains a separator, quote or line break
+fn escape_csv(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Returns `text` as a quoted JSON string
+fn escape_json(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breakdown() -> Breakdown {
+        let mut breakdown = Breakdown::new(&["first", "valid"]);
+        breakdown.add(1, b"a1b2", 12, vec![Value::number(1), Value::Bool(true)]);
+        breakdown.add(2, b"say \"3\", then 4", 34, vec![Value::text("three"), Value::Bool(false)]);
+        breakdown
+    }
+
+    #[test]
+    fn csv_escapes_cells() {
+        assert_eq!(breakdown().to_csv(), "\
+line,raw,contribution,first,valid
+1,a1b2,12,1,true
+2,\"say \"\"3\"\", then 4\",34,three,false
+");
+    }
+
+    #[test]
+    fn json_escapes_strings() {
+        assert_eq!(breakdown().to_json(), "\
+[
+  {\"line\": 1, \"raw\": \"a1b2\", \"contribution\": 12, \"first\": 1, \"valid\": true},
+  {\"line\": 2, \"raw\": \"say \\\"3\\\", then 4\", \"contribution\": 34, \"first\": \"three\", \"valid\": false}
+]
+");
+        assert_eq!(Breakdown::new(&[]).to_json(), "[\n]\n");
+    }
+
+    #[test]
+    fn path_from_args_works() {
+        let args: Vec<String> = ["day1", "--breakdown", "out.csv"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(path_from_args(&args), Some("out.csv"));
+        assert_eq!(path_from_args(&args[..1]), None);
+    }
+
+    #[test]
+    fn breakdown_cant_stream() {
+        let args: Vec<String> = ["day1", "--breakdown", "out.csv", "--stream"].iter().map(|s| s.to_string()).collect();
+        assert!(check_not_streaming(&args).is_err());
+        assert_eq!(check_not_streaming(&args[..3]), Ok(()));
+        assert_eq!(check_not_streaming(&[String::from("day1"), String::from("--stream")]), Ok(()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn rows_need_every_field() {
+        Breakdown::new(&["first"]).add(1, b"", 0, vec![]);
+    }
+}
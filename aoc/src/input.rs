@@ -0,0 +1,444 @@
+//! Byte oriented input helpers.
+//!
+//! The days originally parse their input through `&str` and `char`, which decodes UTF-8 and often allocates a
+//! `String` per token. Puzzle inputs are ASCII, so working on `&[u8]` lets the parsers borrow from the input and
+//! parse numbers in place. Each day that has a byte parser keeps its char parser as well, and
+//! [`compare_parsers`] checks that both agree and times them against each other.
+//!
+//! Days whose answer is a sum over lines can also stream their input with [`sum_lines`], which only ever holds one
+//! line in memory, so the input can come from stdin or a file much larger than memory.
+
+use std::{
+    convert::Infallible,
+    fmt::{self, Debug, Display},
+    fs::File,
+    io::{self, BufRead, BufReader},
+    ops::AddAssign,
+    time::{Duration, Instant},
+};
+
+/// Splits the input into lines without their line endings, like `str::lines`
+pub fn lines(input: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let is_empty = input.is_empty();
+    let input = input.strip_suffix(b"\n").unwrap_or(input);
+    let mut split = input.split(|&b| b == b'\n');
+
+    // An empty input has no lines, rather than one empty line
+    if is_empty {
+        split.next();
+    }
+
+    split.map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+}
+
+/// An error at the first byte that isn't ASCII. Puzzle inputs are ASCII, and the byte parsers read one byte as one
+/// character, so they check this rather than misread a multi-byte character.
+pub fn check_ascii(input: &[u8]) -> Result<(), String> {
+    for (i, line) in lines(input).enumerate() {
+        if let Some(column) = line.iter().position(|b| !b.is_ascii()) {
+            return Err(format!("Line {}: the input should be ASCII, found a byte {:#04x} at byte {}", i + 1, line[column], column + 1));
+        }
+    }
+    Ok(())
+}
+
+/// Splits `bytes` around the first occurrence of `separator`
+pub fn split_once(bytes: &[u8], separator: u8) -> Option<(&[u8], &[u8])> {
+    let position = bytes.iter().position(|&b| b == separator)?;
+    Some((&bytes[..position], &bytes[position + 1..]))
+}
+
+/// Parses an unsigned decimal number. Returns `None` for empty input, any non-digit or on overflow
+pub fn parse_u64(bytes: &[u8]) -> Option<u64> {
+    if bytes.is_empty() {
+        return None;
+    }
+
+    let mut value: u64 = 0;
+    for &b in bytes {
+        if !b.is_ascii_digit() {
+            return None;
+        }
+        value = value.checked_mul(10)?.checked_add((b - b'0') as u64)?;
+    }
+
+    Some(value)
+}
+
+/// Parses a decimal number with an optional leading `-`
+pub fn parse_i64(bytes: &[u8]) -> Option<i64> {
+    match bytes.strip_prefix(b"-") {
+        Some(digits) => {
+            let value = parse_u64(digits)?;
+            if value == i64::MIN.unsigned_abs() {
+                Some(i64::MIN)
+            } else {
+                i64::try_from(value).ok().map(|value| -value)
+            }
+        },
+        None => parse_u64(bytes).and_then(|value| i64::try_from(value).ok()),
+    }
+}
+
+/// A token that should have been a number but isn't one, or doesn't fit in an i64
+#[derive(Debug, PartialEq)]
+pub struct InvalidNumber(pub String);
+
+impl Display for InvalidNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "`{}` is not a number that fits in an i64", self.0)
+    }
+}
+
+/// Iterator over every number in a byte slice, see [`numbers`]
+pub struct Numbers<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Iterator for Numbers<'_> {
+    type Item = Result<i64, InvalidNumber>;
+
+    fn next(&mut self) -> Option<Result<i64, InvalidNumber>> {
+        let bytes = self.bytes;
+        loop {
+            let start = self.position;
+            if start >= bytes.len() {
+                return None;
+            }
+
+            let negative = bytes[start] == b'-' && bytes.get(start + 1).is_some_and(|b| b.is_ascii_digit());
+            if !negative && !bytes[start].is_ascii_digit() {
+                self.position += 1;
+                continue;
+            }
+
+            let mut end = if negative { start + 1 } else { start };
+            while end < bytes.len() && bytes[end].is_ascii_digit() {
+                end += 1;
+            }
+
+            self.position = end;
+            let token = &bytes[start..end];
+            return Some(parse_i64(token).ok_or_else(|| InvalidNumber(String::from_utf8_lossy(token).to_string())));
+        }
+    }
+}
+
+/// Finds every number in `bytes`, skipping whatever separates them.
+/// A `-` directly in front of a digit makes the number negative, and a number too large for an i64 is an error.
+pub fn numbers(bytes: &[u8]) -> Numbers<'_> {
+    Numbers { bytes, position: 0 }
+}
+
+/// Splits `bytes` on ASCII whitespace, skipping empty tokens, like `str::split_whitespace` on ASCII text
+pub fn words(bytes: &[u8]) -> impl Iterator<Item = &[u8]> {
+    bytes.split(|b| b.is_ascii_whitespace()).filter(|word| !word.is_empty())
+}
+
+/// Parses every whitespace separated token as an i64, failing on the first that isn't one
+pub fn parse_numbers(bytes: &[u8]) -> Result<Vec<i64>, InvalidNumber> {
+    words(bytes)
+        .map(|word| parse_i64(word).ok_or_else(|| InvalidNumber(String::from_utf8_lossy(word).to_string())))
+        .collect()
+}
+
+/// Runs both parsers on the same input, checks they agree and prints the fastest time of each.
+/// The parsed result may borrow from the input.
+pub fn compare_parsers<'a, T, C, B>(input: &'a str, runs: usize, by_chars: C, by_bytes: B)
+where
+    T: PartialEq + Debug,
+    C: Fn(&'a str) -> T,
+    B: Fn(&'a [u8]) -> T,
+{
+    let expected = by_chars(input);
+    let actual = by_bytes(input.as_bytes());
+    assert_eq!(expected, actual, "The byte parser should produce the same result as the char parser");
+
+    let chars_time = time_fastest(runs, || by_chars(input));
+    let bytes_time = time_fastest(runs, || by_bytes(input.as_bytes()));
+
+    print_times("Parse", ("chars", chars_time), ("bytes", bytes_time));
+}
+
+/// Runs two implementations on the same input, checks they agree and prints the fastest time of each.
+/// The speedup is how much faster the second one is.
+pub fn compare_implementations<'a, I, T, A, B>(input: &'a I, runs: usize, (a_name, a): (&str, A), (b_name, b): (&str, B))
+where
+    I: ?Sized,
+    T: PartialEq + Debug,
+    A: Fn(&'a I) -> T,
+    B: Fn(&'a I) -> T,
+{
+    assert_eq!(a(input), b(input), "{} and {} should produce the same result", a_name, b_name);
+
+    let a_time = time_fastest(runs, || a(input));
+    let b_time = time_fastest(runs, || b(input));
+
+    print_times("Time", (a_name, a_time), (b_name, b_time));
+}
+
+fn print_times(label: &str, (a_name, a_time): (&str, Duration), (b_name, b_time): (&str, Duration)) {
+    println!("{}[{}]: {:.2?}", label, a_name, a_time);
+    println!("{}[{}]: {:.2?}", label, b_name, b_time);
+    println!("Speedup: {:.2}x", a_time.as_secs_f64() / b_time.as_secs_f64().max(f64::EPSILON));
+}
+
+/// Where a streaming run reads its input from, taken from `--stream [<file>|-]` and `--report-every <lines>`
+#[derive(Debug, PartialEq)]
+pub struct StreamArgs {
+    /// `-` reads from stdin
+    pub path: String,
+    pub report_every: Option<u64>,
+}
+
+impl StreamArgs {
+    /// Returns `None` unless `--stream` was passed. Without a file after it the day's `default_path` is streamed.
+    pub fn from_args(args: &[String], default_path: &str) -> Result<Option<StreamArgs>, String> {
+        let Some(position) = args.iter().position(|arg| arg == "--stream") else {
+            return Ok(None);
+        };
+        let path = match args.get(position + 1) {
+            Some(path) if path == "-" || !path.starts_with("--") => path.clone(),
+            _ => String::from(default_path),
+        };
+
+        let report_every = match args.iter().position(|arg| arg == "--report-every") {
+            Some(position) => match args.get(position + 1).map(|lines| lines.parse::<u64>()) {
+                Some(Ok(lines)) if lines > 0 => Some(lines),
+                Some(_) => return Err(String::from("--report-every needs a positive number of lines")),
+                None => return Err(String::from("--report-every needs a value")),
+            },
+            None => None,
+        };
+
+        Ok(Some(StreamArgs { path, report_every }))
+    }
+
+    pub fn open(&self) -> io::Result<Box<dyn BufRead>> {
+        if self.path == "-" {
+            Ok(Box::new(io::stdin().lock()))
+        } else {
+            Ok(Box::new(BufReader::new(File::open(&self.path)?)))
+        }
+    }
+
+    /// Opens the input and sums `line_value` over its lines, see [`sum_lines`]
+    pub fn sum_lines<T, F>(&self, line_value: F) -> io::Result<T>
+    where
+        T: Default + AddAssign + Display + Copy,
+        F: FnMut(&[u8]) -> T,
+    {
+        sum_lines(self.open()?, self.report_every, line_value)
+    }
+
+    /// Opens the input and folds `add_line` over its lines, see [`try_fold_lines`]
+    pub fn try_fold_lines<T, E, F>(&self, init: T, add_line: F) -> io::Result<Result<T, E>>
+    where
+        T: Display,
+        F: FnMut(T, &[u8]) -> Result<T, E>,
+    {
+        try_fold_lines(self.open()?, self.report_every, init, add_line)
+    }
+}
+
+/// Sums `line_value` over every line of `reader`, with lines split the same way as [`lines`].
+/// The line buffer is reused, so memory use is bounded by the longest line rather than the input.
+/// With `report_every`, the line count and running total are printed to stderr every that many lines.
+pub fn sum_lines<R, T, F>(reader: R, report_every: Option<u64>, mut line_value: F) -> io::Result<T>
+where
+    R: BufRead,
+    T: Default + AddAssign + Display + Copy,
+    F: FnMut(&[u8]) -> T,
+{
+    let Ok(total) = try_fold_lines(reader, report_every, T::default(), |mut total, line| {
+        total += line_value(line);
+        Ok::<T, Infallible>(total)
+    })?;
+    Ok(total)
+}
+
+/// Like [`sum_lines`], but `add_line` takes the running total and a line and returns the new total, so it can check
+/// for overflow. The first error it returns stops the stream and is returned as the inner `Err`.
+pub fn try_fold_lines<R, T, E, F>(mut reader: R, report_every: Option<u64>, init: T, mut add_line: F) -> io::Result<Result<T, E>>
+where
+    R: BufRead,
+    T: Display,
+    F: FnMut(T, &[u8]) -> Result<T, E>,
+{
+    let mut total = init;
+    let mut line = Vec::new();
+    let mut count: u64 = 0;
+
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        let mut bytes = line.strip_suffix(b"\n").unwrap_or(&line);
+        bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+
+        total = match add_line(total, bytes) {
+            Ok(total) => total,
+            Err(error) => return Ok(Err(error)),
+        };
+        count += 1;
+        line.clear();
+
+        if report_every.is_some_and(|every| count.is_multiple_of(every)) {
+            eprintln!("Lines: {} Running total: {}", count, total);
+        }
+    }
+
+    Ok(Ok(total))
+}
+
+fn time_fastest<T>(runs: usize, f: impl Fn() -> T) -> Duration {
+    let mut fastest = Duration::MAX;
+    for _ in 0..runs.max(1) {
+        let now = Instant::now();
+        let result = f();
+        fastest = fastest.min(now.elapsed());
+        drop(result);
+    }
+    fastest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_match_str_lines() {
+        for input in ["", "\n", "a", "a\nb", "a\nb\n", "a\r\nb\r\n", "a\n\nb", "\n\n"] {
+            let expected: Vec<&[u8]> = input.lines().map(|line| line.as_bytes()).collect();
+            let actual: Vec<&[u8]> = lines(input.as_bytes()).collect();
+            assert_eq!(expected, actual, "input {:?}", input);
+        }
+    }
+
+    #[test]
+    fn check_ascii_finds_the_first_other_byte() {
+        assert_eq!(check_ascii(b"abc\n#.1\n"), Ok(()));
+        assert_eq!(check_ascii("ab\n.\u{e9}.".as_bytes()), Err(String::from("Line 2: the input should be ASCII, found a byte 0xc3 at byte 2")));
+    }
+
+    #[test]
+    fn split_once_works() {
+        assert_eq!(split_once(b"Card 1: 2 | 3", b':'), Some((&b"Card 1"[..], &b" 2 | 3"[..])));
+        assert_eq!(split_once(b"no separator", b':'), None);
+    }
+
+    #[test]
+    fn parse_u64_works() {
+        assert_eq!(parse_u64(b"0"), Some(0));
+        assert_eq!(parse_u64(b"1234"), Some(1234));
+        assert_eq!(parse_u64(b"18446744073709551615"), Some(u64::MAX));
+        assert_eq!(parse_u64(b"18446744073709551616"), None);
+        assert_eq!(parse_u64(b""), None);
+        assert_eq!(parse_u64(b"12a"), None);
+        assert_eq!(parse_u64(b"-1"), None);
+    }
+
+    #[test]
+    fn parse_i64_works() {
+        assert_eq!(parse_i64(b"-12"), Some(-12));
+        assert_eq!(parse_i64(b"12"), Some(12));
+        assert_eq!(parse_i64(b"-9223372036854775808"), Some(i64::MIN));
+        assert_eq!(parse_i64(b"9223372036854775808"), None);
+        assert_eq!(parse_i64(b"-"), None);
+    }
+
+    #[test]
+    fn numbers_finds_every_number() {
+        let found: Result<Vec<i64>, _> = numbers(b"Time:      7  15   30").collect();
+        assert_eq!(found, Ok(vec![7, 15, 30]));
+
+        let found: Result<Vec<i64>, _> = numbers(b"0 -3 -6 a-b 9-").collect();
+        assert_eq!(found, Ok(vec![0, -3, -6, 9]));
+
+        assert_eq!(numbers(b"").count(), 0);
+    }
+
+    #[test]
+    fn numbers_too_large_are_errors() {
+        let found: Vec<Result<i64, InvalidNumber>> = numbers(b"1 99999999999999999999 3 4").collect();
+        assert_eq!(found, vec![Ok(1), Err(InvalidNumber(String::from("99999999999999999999"))), Ok(3), Ok(4)]);
+        assert_eq!(numbers(b"1 99999999999999999999 3 4").collect::<Result<Vec<i64>, _>>(),
+            Err(InvalidNumber(String::from("99999999999999999999"))));
+    }
+
+    #[test]
+    fn parse_numbers_rejects_other_tokens() {
+        assert_eq!(parse_numbers(b" 1  -2\t3 "), Ok(vec![1, -2, 3]));
+        assert_eq!(parse_numbers(b""), Ok(vec![]));
+        assert_eq!(parse_numbers(b"1 2 x 3"), Err(InvalidNumber(String::from("x"))));
+        assert_eq!(parse_numbers(b"1 2-3"), Err(InvalidNumber(String::from("2-3"))));
+        assert_eq!(parse_numbers(b"9223372036854775808"), Err(InvalidNumber(String::from("9223372036854775808"))));
+    }
+
+    #[test]
+    fn sum_lines_matches_lines() {
+        for input in ["", "\n", "1\n2", "1\r\n2\r\n", "1\n\n3\n"] {
+            let expected: usize = lines(input.as_bytes()).map(|line| line.len() + 1).sum();
+            let actual = sum_lines(input.as_bytes(), None, |line| line.len() + 1).expect("Test - read");
+            assert_eq!(expected, actual, "input {:?}", input);
+        }
+    }
+
+    #[test]
+    fn sum_lines_sums_numbers() {
+        let total = sum_lines(&b"1 2\n-3\n10"[..], Some(1), |line| numbers(line).map(|number| number.expect("Test - number")).sum::<i64>()).expect("Test - read");
+        assert_eq!(total, 10);
+    }
+
+    #[test]
+    fn try_fold_lines_stops_at_the_first_error() {
+        let checked_sum = |input: &[u8]| try_fold_lines(input, None, 0u8, |total, line| {
+            total.checked_add(parse_u64(line).expect("Test - number") as u8).ok_or(String::from("overflow"))
+        }).expect("Test - read");
+
+        assert_eq!(checked_sum(b"100\n100\n"), Ok(200));
+        assert_eq!(checked_sum(b"100\n100\n100\nnot a number\n"), Err(String::from("overflow")));
+    }
+
+    #[test]
+    fn stream_args_from_args() {
+        let args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+
+        assert_eq!(StreamArgs::from_args(&args(&["day1"]), "input.txt"), Ok(None));
+        assert_eq!(StreamArgs::from_args(&args(&["day1", "--stream"]), "input.txt"),
+            Ok(Some(StreamArgs { path: String::from("input.txt"), report_every: None })));
+        assert_eq!(StreamArgs::from_args(&args(&["day1", "--stream", "-", "--report-every", "1000"]), "input.txt"),
+            Ok(Some(StreamArgs { path: String::from("-"), report_every: Some(1000) })));
+        assert_eq!(StreamArgs::from_args(&args(&["day1", "--stream", "--report-every", "5"]), "input.txt"),
+            Ok(Some(StreamArgs { path: String::from("input.txt"), report_every: Some(5) })));
+        assert_eq!(StreamArgs::from_args(&args(&["day1", "--stream", "big.txt"]), "input.txt"),
+            Ok(Some(StreamArgs { path: String::from("big.txt"), report_every: None })));
+    }
+
+    #[test]
+    fn bad_report_every_is_an_error() {
+        let args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+
+        for bad in [&["day1", "--stream", "--report-every", "lots"][..], &["day1", "--stream", "--report-every", "0"],
+            &["day1", "--stream", "--report-every"]] {
+            assert!(StreamArgs::from_args(&args(bad), "input.txt").is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn compare_parsers_accepts_matching_parsers() {
+        compare_parsers("1 2 3", 2,
+            |input: &str| input.split(' ').map(|x| x.parse::<i64>().unwrap()).collect::<Vec<i64>>(),
+            |input: &[u8]| parse_numbers(input).expect("Test - numbers"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn compare_implementations_rejects_different_results() {
+        compare_implementations(&b"1 2"[..], 1, ("sum", |input: &[u8]| numbers(input).flatten().sum::<i64>()), ("count", |input: &[u8]| numbers(input).count() as i64));
+    }
+
+    #[test]
+    #[should_panic]
+    fn compare_parsers_rejects_different_results() {
+        compare_parsers("1 2 3", 1, |_: &str| 1, |_: &[u8]| 2);
+    }
+}
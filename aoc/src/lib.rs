@@ -0,0 +1,40 @@
+use std::{fmt::Display, process};
+
+pub mod alloc;
+pub mod breakdown;
+pub mod input;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
+
+/// Prints the error and exits, for errors in the input or the options rather than bugs
+pub fn unwrap_or_exit<T, E: Display>(result: Result<T, E>) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    })
+}
+
+/// Escapes the characters that mean something in HTML text and attribute values
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_html_works() {
+        assert_eq!(escape_html("a < b && \"c\" > d"), "a &lt; b &amp;&amp; &quot;c&quot; &gt; d");
+    }
+}
@@ -0,0 +1,123 @@
+use std::{env, fs, path::Path, process};
+
+mod bench;
+mod report;
+mod runner;
+
+const USAGE: &str = "\
+Usage: aoc report [--root <dir>] [--out <file>]
+       aoc bench [--root <dir>] [--runs <n>]
+       aoc day<N>[<part>] [<args>...]    e.g. aoc day2 query --percent 90";
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    match args.get(1).map(|s| s.as_str()) {
+        Some("report") => {
+            let root = get_option(&args, "--root").unwrap_or(".");
+            let out = get_option(&args, "--out").unwrap_or("report.html");
+            run_report(Path::new(root), Path::new(out));
+        }
+        Some("bench") => {
+            let root = get_option(&args, "--root").unwrap_or(".");
+            let runs = aoc::unwrap_or_exit(get_runs(&args));
+            run_bench(Path::new(root), runs);
+        }
+        Some(name) if name.starts_with("day") => run_day_command(name, &args[2..]),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(1);
+        }
+    }
+}
+
+fn run_report(root: &Path, out: &Path) {
+    let days = find_days_or_exit(root);
+
+    let mut runs = Vec::new();
+    for day in days {
+        println!("Running {}...", day.name);
+        runs.push(runner::run_day(day));
+    }
+
+    let html = report::render(&runs);
+    fs::write(out, html).expect("Should have been able to write the report");
+
+    println!("Report written to {}", out.display());
+}
+
+fn run_bench(root: &Path, runs: usize) {
+    let days = find_days_or_exit(root);
+
+    let mut benches = Vec::new();
+    for day in days {
+        eprintln!("Benchmarking {}...", day.name);
+        benches.push(bench::bench_day(day, runs));
+    }
+
+    print!("{}", bench::render(&benches));
+}
+
+/// Builds a day and runs it from its directory with the remaining arguments, exiting with its status
+fn run_day_command(name: &str, args: &[String]) {
+    let days = find_days_or_exit(Path::new("."));
+    let day = runner::find_day(days, name).unwrap_or_else(|| {
+        eprintln!("No directory found for {}", name);
+        process::exit(1);
+    });
+
+    let binary = aoc::unwrap_or_exit(runner::build_day(&day, false));
+    let status = aoc::unwrap_or_exit(runner::run_with_args(&day, &binary, args));
+    process::exit(status.code().unwrap_or(1));
+}
+
+fn find_days_or_exit(root: &Path) -> Vec<runner::Day> {
+    let days = runner::find_days(root);
+    if days.is_empty() {
+        eprintln!("No day directories found in {}", root.display());
+        process::exit(1);
+    }
+    days
+}
+
+/// Returns the value following `name` in the argument list, e.g. `--out report.html`
+fn get_option<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    let position = args.iter().position(|arg| arg == name)?;
+    args.get(position + 1).map(|s| s.as_str())
+}
+
+/// Reads `--runs <n>`, 5 if it isn't given
+fn get_runs(args: &[String]) -> Result<usize, String> {
+    match get_option(args, "--runs").map(|runs| runs.parse::<usize>()) {
+        Some(Ok(runs)) if runs > 0 => Ok(runs),
+        Some(_) => Err(String::from("--runs needs a positive number of runs")),
+        None => Ok(5),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_option_works() {
+        let args: Vec<String> = ["aoc", "report", "--out", "x.html"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(get_option(&args, "--out"), Some("x.html"));
+        assert_eq!(get_option(&args, "--root"), None);
+    }
+
+    #[test]
+    fn get_option_without_value() {
+        let args: Vec<String> = ["aoc", "report", "--out"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(get_option(&args, "--out"), None);
+    }
+
+    #[test]
+    fn get_runs_works() {
+        let args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+        assert_eq!(get_runs(&args(&["aoc", "bench"])), Ok(5));
+        assert_eq!(get_runs(&args(&["aoc", "bench", "--runs", "3"])), Ok(3));
+        assert!(get_runs(&args(&["aoc", "bench", "--runs", "three"])).is_err());
+        assert!(get_runs(&args(&["aoc", "bench", "--runs", "0"])).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+use std::fmt::Write;
+
+use aoc::escape_html;
+
+use crate::runner::{DayRun, Outcome};
+
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em auto; max-width: 60em; color: #222; }
+section { border-top: 1px solid #ccc; padding-top: 1em; }
+pre { background: #f4f4f4; padding: 0.5em; overflow-x: auto; }
+.answer { font-size: 1.2em; font-weight: bold; }
+.error { color: #b00; }
+.visual svg { max-width: 100%; height: auto; border: 1px solid #ccc; }
+";
+
+/// Renders every run into one self-contained HTML page, with a section per day.
+/// Visuals are inlined as SVG so the page has no external assets.
+pub fn render(runs: &[DayRun]) -> String {
+    let mut html = String::new();
+    writeln!(html, "<!DOCTYPE html>").unwrap();
+    writeln!(html, "<html><head><meta charset=\"utf-8\"><title>Advent of Code 2023</title>").unwrap();
+    writeln!(html, "<style>{}</style></head><body>", STYLE).unwrap();
+    writeln!(html, "<h1>Advent of Code 2023</h1>").unwrap();
+
+    let mut current_day = None;
+    for run in runs {
+        if current_day != Some(run.day.number) {
+            if current_day.is_some() {
+                writeln!(html, "</section>").unwrap();
+            }
+            writeln!(html, "<section id=\"day{}\"><h2>Day {}</h2>", run.day.number, run.day.number).unwrap();
+            current_day = Some(run.day.number);
+        }
+
+        render_run(&mut html, run);
+    }
+
+    if current_day.is_some() {
+        writeln!(html, "</section>").unwrap();
+    }
+
+    writeln!(html, "</body></html>").unwrap();
+    html
+}
+
+fn render_run(html: &mut String, run: &DayRun) {
+    writeln!(html, "<h3>Part {} <small>({})</small></h3>", run.day.part, run.day.name).unwrap();
+
+    match &run.outcome {
+        Outcome::MissingInput => {
+            writeln!(html, "<p class=\"error\">No input.txt found</p>").unwrap();
+        },
+        Outcome::BuildFailed(message) => {
+            writeln!(html, "<p class=\"error\">Could not run</p><pre>{}</pre>", escape_html(message)).unwrap();
+        },
+        Outcome::Ran { stdout, stderr, success, elapsed, visual } => {
+            match get_answer_line(stdout) {
+                Some(answer) => writeln!(html, "<p class=\"answer\">{}</p>", escape_html(answer)).unwrap(),
+                None => writeln!(html, "<p class=\"error\">No output</p>").unwrap(),
+            }
+
+            writeln!(html, "<p>Time: {:?}</p>", elapsed).unwrap();
+
+            if !success {
+                writeln!(html, "<p class=\"error\">Exited with an error</p><pre>{}</pre>", escape_html(stderr)).unwrap();
+            }
+
+            writeln!(html, "<details><summary>Output</summary><pre>{}</pre></details>", escape_html(&get_output_tail(stdout))).unwrap();
+
+            if let Some(svg) = visual {
+                writeln!(html, "<div class=\"visual\">{}</div>", svg).unwrap();
+            }
+        },
+    }
+}
+
+/// The days print their answer last, after any debugging output
+fn get_answer_line(stdout: &str) -> Option<&str> {
+    stdout.lines()
+        .map(|line| line.trim())
+        .rfind(|line| !line.is_empty())
+}
+
+const MAX_OUTPUT_LINES: usize = 50;
+
+/// Some days print a line per step, so only the end of the output is kept
+fn get_output_tail(stdout: &str) -> String {
+    let lines: Vec<&str> = stdout.lines().collect();
+    if lines.len() <= MAX_OUTPUT_LINES {
+        return lines.join("\n");
+    }
+
+    let omitted = lines.len() - MAX_OUTPUT_LINES;
+    format!("... {} lines omitted\n{}", omitted, lines[omitted..].join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, time::Duration};
+
+    use super::*;
+    use crate::runner::Day;
+
+    fn day_run(number: u32, part: char, outcome: Outcome) -> DayRun {
+        DayRun {
+            day: Day { name: format!("day{}{}", number, part), number, part, path: PathBuf::new() },
+            outcome,
+        }
+    }
+
+    #[test]
+    fn answer_is_last_line() {
+        assert_eq!(get_answer_line("debug\nAnswer: 42\n\n"), Some("Answer: 42"));
+        assert_eq!(get_answer_line("Sum of parts: 7"), Some("Sum of parts: 7"));
+        assert_eq!(get_answer_line("\n"), None);
+    }
+
+    #[test]
+    fn output_tail_is_truncated() {
+        let stdout: String = (0..60).map(|i| format!("{}\n", i)).collect();
+        let tail = get_output_tail(&stdout);
+        assert!(tail.starts_with("... 10 lines omitted\n10\n"));
+        assert!(tail.ends_with("59"));
+        assert_eq!(get_output_tail("a\nb\n"), "a\nb");
+    }
+
+    #[test]
+    fn render_groups_parts_by_day() {
+        let runs = vec![
+            day_run(1, 'a', Outcome::Ran {
+                stdout: String::from("Answer: <1>"),
+                stderr: String::new(),
+                success: true,
+                elapsed: Duration::from_millis(1),
+                visual: Some(String::from("<svg></svg>")),
+            }),
+            day_run(1, 'b', Outcome::MissingInput),
+            day_run(2, 'a', Outcome::BuildFailed(String::from("oops"))),
+        ];
+
+        let html = render(&runs);
+
+        assert_eq!(html.matches("<section").count(), 2);
+        assert_eq!(html.matches("</section>").count(), 2);
+        assert!(html.contains("Answer: &lt;1&gt;"));
+        assert!(html.contains("<svg></svg>"));
+        assert!(html.contains("No input.txt found"));
+        assert!(html.contains("oops"));
+    }
+}
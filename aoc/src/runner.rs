@@ -0,0 +1,214 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus},
+    time::{Duration, Instant},
+};
+
+/// Name of the file a day writes its visualization to, if it has one.
+/// The file is removed before every run so a stale picture is never reported.
+pub const VISUAL_FILE: &str = "report.svg";
+
+pub struct Day {
+    pub name: String,
+    pub number: u32,
+    pub part: char,
+    pub path: PathBuf,
+}
+
+pub enum Outcome {
+    MissingInput,
+    BuildFailed(String),
+    Ran {
+        stdout: String,
+        stderr: String,
+        success: bool,
+        elapsed: Duration,
+        visual: Option<String>,
+    },
+}
+
+pub struct DayRun {
+    pub day: Day,
+    pub outcome: Outcome,
+}
+
+/// Finds every `day<N><part>` directory under `root`, ordered by day and then part
+pub fn find_days(root: &Path) -> Vec<Day> {
+    let entries = match fs::read_dir(root) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut days: Vec<Day> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().join("Cargo.toml").exists())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let (number, part) = parse_day_name(&name)?;
+            Some(Day { name, number, part, path: entry.path() })
+        })
+        .collect();
+
+    days.sort_by_key(|day| (day.number, day.part));
+    days
+}
+
+/// Splits a directory name such as `day10b` into `(10, 'b')`
+fn parse_day_name(name: &str) -> Option<(u32, char)> {
+    let rest = name.strip_prefix("day")?;
+    let part = rest.chars().last()?;
+    if !part.is_ascii_lowercase() {
+        return None;
+    }
+
+    let number = rest[..rest.len() - 1].parse::<u32>().ok()?;
+    Some((number, part))
+}
+
+/// Reads the package name out of a day's Cargo.toml, which is not always the directory name
+fn package_name(day_path: &Path) -> Option<String> {
+    let manifest = fs::read_to_string(day_path.join("Cargo.toml")).ok()?;
+    manifest.lines()
+        .find(|line| line.trim_start().starts_with("name"))
+        .and_then(|line| line.split('"').nth(1))
+        .map(|name| name.to_string())
+}
+
+/// Returns true when the day can be built with the counting allocator from `aoc::alloc`
+pub fn has_alloc_stats(day: &Day) -> bool {
+    fs::read_to_string(day.path.join("Cargo.toml"))
+        .map(|manifest| manifest.contains("alloc-stats"))
+        .unwrap_or(false)
+}
+
+/// Builds a day in release mode and returns the path to its binary.
+/// Builds with allocation stats go to their own target directory so they don't invalidate the plain build.
+pub fn build_day(day: &Day, alloc_stats: bool) -> Result<PathBuf, String> {
+    let package = package_name(&day.path).ok_or(String::from("Could not read package name"))?;
+
+    let target_dir = if alloc_stats {
+        day.path.join("target").join("alloc-stats")
+    } else {
+        day.path.join("target")
+    };
+
+    let mut command = Command::new("cargo");
+    command.args(["build", "--release", "--quiet", "--target-dir"])
+        .arg(&target_dir)
+        .current_dir(&day.path);
+    if alloc_stats {
+        command.args(["--features", "alloc-stats"]);
+    }
+
+    let output = command.output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+
+    Ok(target_dir
+        .join("release")
+        .join(format!("{}{}", package, env::consts::EXE_SUFFIX)))
+}
+
+/// Runs a built day against its `input.txt`, timing only the run itself
+pub fn run_binary(day: &Day, binary: &Path) -> Outcome {
+    let visual_path = day.path.join(VISUAL_FILE);
+    let _ = fs::remove_file(&visual_path);
+
+    // day2a reads its input path from the first argument, the other days ignore it
+    let now = Instant::now();
+    let output = Command::new(binary)
+        .arg("input.txt")
+        .current_dir(&day.path)
+        .output();
+    let elapsed = now.elapsed();
+
+    match output {
+        Ok(output) => Outcome::Ran {
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            success: output.status.success(),
+            elapsed,
+            visual: fs::read_to_string(&visual_path).ok(),
+        },
+        Err(e) => Outcome::BuildFailed(e.to_string()),
+    }
+}
+
+/// Picks the day a command line names: `day10b` is that exact directory and `day10` the first part found for day 10
+pub fn find_day(days: Vec<Day>, name: &str) -> Option<Day> {
+    if let Some(number) = name.strip_prefix("day").and_then(|number| number.parse::<u32>().ok()) {
+        return days.into_iter().find(|day| day.number == number);
+    }
+    days.into_iter().find(|day| day.name == name)
+}
+
+/// Runs a built day in its directory with the given arguments, sharing this process's terminal
+pub fn run_with_args(day: &Day, binary: &Path, args: &[String]) -> Result<ExitStatus, String> {
+    Command::new(binary)
+        .args(args)
+        .current_dir(&day.path)
+        .status()
+        .map_err(|e| e.to_string())
+}
+
+/// Builds a day in release mode and runs it once against its `input.txt`
+pub fn run_day(day: Day) -> DayRun {
+    if !day.path.join("input.txt").exists() {
+        return DayRun { day, outcome: Outcome::MissingInput };
+    }
+
+    let outcome = match build_day(&day, false) {
+        Ok(binary) => run_binary(&day, &binary),
+        Err(e) => Outcome::BuildFailed(e),
+    };
+
+    DayRun { day, outcome }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_day_name_works() {
+        assert_eq!(parse_day_name("day1a"), Some((1, 'a')));
+        assert_eq!(parse_day_name("day10b"), Some((10, 'b')));
+        assert_eq!(parse_day_name("aoc"), None);
+        assert_eq!(parse_day_name("day"), None);
+        assert_eq!(parse_day_name("dayxa"), None);
+        assert_eq!(parse_day_name("day12"), None);
+    }
+
+    #[test]
+    fn days_are_sorted_numerically() {
+        let root = env::temp_dir().join("aoc_find_days_test");
+        let _ = fs::remove_dir_all(&root);
+        for name in ["day10a", "day2b", "day2a", "day1a", "notes"] {
+            fs::create_dir_all(root.join(name)).expect("Test - create dir");
+            fs::write(root.join(name).join("Cargo.toml"), "").expect("Test - write manifest");
+        }
+
+        let names: Vec<String> = find_days(&root).into_iter().map(|day| day.name).collect();
+        assert_eq!(names, vec!["day1a", "day2a", "day2b", "day10a"]);
+
+        fs::remove_dir_all(&root).expect("Test - cleanup");
+    }
+
+    #[test]
+    fn find_day_takes_the_first_part() {
+        let days = || ["day2a", "day2b", "day10a"].iter()
+            .map(|name| {
+                let (number, part) = parse_day_name(name).expect("Test - name");
+                Day { name: name.to_string(), number, part, path: PathBuf::from(name) }
+            })
+            .collect::<Vec<Day>>();
+
+        assert_eq!(find_day(days(), "day2").map(|day| day.name), Some(String::from("day2a")));
+        assert_eq!(find_day(days(), "day2b").map(|day| day.name), Some(String::from("day2b")));
+        assert_eq!(find_day(days(), "day10").map(|day| day.name), Some(String::from("day10a")));
+        assert!(find_day(days(), "day3").is_none());
+        assert!(find_day(days(), "day10c").is_none());
+    }
+}
@@ -0,0 +1,55 @@
+//! Helpers for tests that check a solver against a slower reference on generated inputs. Only built with the
+//! `test-util` feature, which the days turn on in their `[dev-dependencies]`.
+
+use std::{fmt::Debug, panic::{self, AssertUnwindSafe}};
+
+/// A fixed linear congruential generator, so generated test inputs are the same on every run
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    /// A number below `limit`
+    pub fn below(&mut self, limit: u64) -> u64 {
+        self.state = self.state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.state >> 33) % limit
+    }
+}
+
+/// Makes `cases` inputs with `generate`, which gets the generator and the case number, and runs `check` on each.
+/// A failing check is reported with the case and the input it failed on.
+pub fn check_generated<T: Debug>(seed: u64, cases: usize, mut generate: impl FnMut(&mut Rng, usize) -> T, mut check: impl FnMut(&T)) {
+    let mut rng = Rng::new(seed);
+    for case in 0..cases {
+        let input = generate(&mut rng, case);
+        if panic::catch_unwind(AssertUnwindSafe(|| check(&input))).is_err() {
+            panic!("Generated case {} of seed {} failed on {:?}", case, seed, input);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_numbers() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        for _ in 0..10 {
+            let number = a.below(100);
+            assert!(number < 100);
+            assert_eq!(number, b.below(100));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Generated case 3 of seed 1 failed on 3")]
+    fn failures_name_the_case() {
+        check_generated(1, 5, |_, case| case, |&case| assert!(case < 3));
+    }
+}
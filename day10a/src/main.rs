@@ -1,12 +1,19 @@
-use std::{collections::{HashMap, HashSet}, fs, ops::Add};
+use std::{collections::{HashMap, HashSet}, env, fs, ops::Add};
 
+use aoc::input;
 use lazy_static::lazy_static;
 
 fn main() {
     let contents = fs::read_to_string("input.txt")
         .expect("Something went wrong reading the file");
+    aoc::unwrap_or_exit(input::check_ascii(contents.as_bytes()));
 
-    let map: Map = Map::new(contents);
+    if env::args().any(|arg| arg == "--compare-parsers") {
+        input::compare_parsers(&contents, 100, |contents| Map::new(contents.to_string()), Map::from_bytes);
+        return;
+    }
+
+    let map: Map = Map::from_bytes(contents.as_bytes());
 
     let answer = get_furthest_location(&map);
 
@@ -54,7 +61,7 @@ struct Point {
 
 impl Point {
     fn new(x: i32, y: i32) -> Point {
-        Point { x: x, y: y }
+        Point { x, y }
     }
 }
 
@@ -64,12 +71,13 @@ impl<'a> Add<&'a Point> for Point {
     type Output = Point;
     fn add(self, other: &'a Point) -> Point {
         Point {
-            x: self.x + &other.x,
-            y: self.y + &other.y,
+            x: self.x + other.x,
+            y: self.y + other.y,
         }
     }
 }
 
+#[derive(PartialEq, Debug)]
 struct Map {
     map: Vec<Vec<char>>,
     starting_location: Point,
@@ -78,7 +86,16 @@ struct Map {
 impl Map {
     fn new(map: String) -> Map {
         let internal_map = map.split('\n').map(|line| line.chars().collect()).collect();
-        
+        Map::from_grid(internal_map)
+    }
+
+    /// Same as `Map::new`, without decoding UTF-8 or copying the input into a `String` first
+    fn from_bytes(map: &[u8]) -> Map {
+        let internal_map = map.split(|&b| b == b'\n').map(|line| line.iter().map(|&b| b as char).collect()).collect();
+        Map::from_grid(internal_map)
+    }
+
+    fn from_grid(internal_map: Vec<Vec<char>>) -> Map {
         let mut ret_val = Map {
             map: internal_map,
             starting_location: Point::new(0, 0),
@@ -97,14 +114,14 @@ impl Map {
     }
 
     fn get(&self, point: Point) -> char {
-        return self.map[point.y as usize][point.x as usize];
+        self.map[point.y as usize][point.x as usize]
     }
 }
 
 fn get_furthest_location(map: &Map) -> u32 {
     // Travel in both direction until you have visited all the nodes
     let mut visited_nodes : HashSet<Point> = HashSet::new();
-    visited_nodes.insert(map.starting_location.clone());
+    visited_nodes.insert(map.starting_location);
 
     // find starting directions
     let (mut first_direction, mut second_direction) = get_starting_directions(map);
@@ -112,8 +129,8 @@ fn get_furthest_location(map: &Map) -> u32 {
     let mut first_pointer = map.starting_location + &DIRECTION_TO_MOVEMENT[&first_direction];
     let mut second_pointer = map.starting_location + &DIRECTION_TO_MOVEMENT[&second_direction];
 
-    visited_nodes.insert(first_pointer.clone());
-    visited_nodes.insert(second_pointer.clone());
+    visited_nodes.insert(first_pointer);
+    visited_nodes.insert(second_pointer);
 
     let mut distance = 1;
     loop {
@@ -138,14 +155,14 @@ fn get_furthest_location(map: &Map) -> u32 {
             break;
         }
 
-        visited_nodes.insert(first_pointer.clone());
+        visited_nodes.insert(first_pointer);
 
         if visited_nodes.contains(&second_pointer) {
             distance += 1;
             break;
         }
 
-        visited_nodes.insert(second_pointer.clone());
+        visited_nodes.insert(second_pointer);
         distance += 1;
     }
 
@@ -158,8 +175,8 @@ fn get_starting_directions(map: &Map) -> (Direction, Direction) {
     DIRECTION_TO_MOVEMENT.keys().for_each(|direction| {
         let point = &DIRECTION_TO_MOVEMENT[direction];
 
-        let new_x = map.starting_location.x as i32 + point.x;
-        let new_y = map.starting_location.y as i32 + point.y;
+        let new_x = map.starting_location.x + point.x;
+        let new_y = map.starting_location.y + point.y;
 
         if new_x < 0 || new_y < 0 || new_x >= map.map[0].len() as i32 || new_y >= map.map.len() as i32 {
             return;
@@ -233,6 +250,13 @@ mod tests {
         assert_eq!(4, distance);
     }
 
+    #[test]
+    fn test_map_from_bytes() {
+        let contents = String::from(".....\n.S-7.\n.|.|.\n.L-J.\n.....\n");
+
+        assert_eq!(Map::from_bytes(contents.as_bytes()), Map::new(contents));
+    }
+
     #[test]
     fn test_square_loop_starting_location() {
         let contents = String::from(".....\n.S-7.\n.|.|.\n.L-J.\n.....");
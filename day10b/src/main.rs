@@ -1,16 +1,25 @@
-use std::{collections::{HashMap, HashSet, VecDeque}, fs, ops::Add};
+use std::{collections::{HashMap, HashSet, VecDeque}, env, fs::{self, File}, io::Write, ops::Add};
 
+use aoc::input;
 use lazy_static::lazy_static;
 
 fn main() {
     let contents = fs::read_to_string("input.txt")
         .expect("Something went wrong reading the file");
+    aoc::unwrap_or_exit(input::check_ascii(contents.as_bytes()));
 
-    let map: Map = Map::new(contents);
+    if env::args().any(|arg| arg == "--compare-parsers") {
+        input::compare_parsers(&contents, 100, |contents| Map::new(contents.to_string()), Map::from_bytes);
+        return;
+    }
+
+    let map: Map = Map::from_bytes(contents.as_bytes());
 
-    let answer = get_area_of_inside(&map);
+    let (boundary, area) = get_loop_and_inside(&map);
 
-    println!("Answer: {}", answer);
+    write_report_svg(&map, &boundary, &area).expect("Should have been able to write report.svg");
+
+    println!("Answer: {}", area.len());
 }
 
 #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
@@ -23,6 +32,7 @@ enum Direction {
 }
 
 #[derive(PartialEq, Eq, Hash, Debug)]
+#[allow(clippy::enum_variant_names)]
 enum Turn {
     Left,
     Right,
@@ -61,7 +71,7 @@ struct Point {
 
 impl Point {
     fn new(x: i32, y: i32) -> Point {
-        Point { x: x, y: y }
+        Point { x, y }
     }
 }
 
@@ -71,12 +81,13 @@ impl<'a> Add<&'a Point> for Point {
     type Output = Point;
     fn add(self, other: &'a Point) -> Point {
         Point {
-            x: self.x + &other.x,
-            y: self.y + &other.y,
+            x: self.x + other.x,
+            y: self.y + other.y,
         }
     }
 }
 
+#[derive(PartialEq, Debug)]
 struct Map {
     map: Vec<Vec<char>>,
     starting_location: Point,
@@ -85,7 +96,16 @@ struct Map {
 impl Map {
     fn new(map: String) -> Map {
         let internal_map = map.split('\n').map(|line| line.chars().collect()).collect();
-        
+        Map::from_grid(internal_map)
+    }
+
+    /// Same as `Map::new`, without decoding UTF-8 or copying the input into a `String` first
+    fn from_bytes(map: &[u8]) -> Map {
+        let internal_map = map.split(|&b| b == b'\n').map(|line| line.iter().map(|&b| b as char).collect()).collect();
+        Map::from_grid(internal_map)
+    }
+
+    fn from_grid(internal_map: Vec<Vec<char>>) -> Map {
         let mut ret_val = Map {
             map: internal_map,
             starting_location: Point::new(0, 0),
@@ -104,11 +124,12 @@ impl Map {
     }
 
     fn get(&self, point: Point) -> char {
-        return self.map[point.y as usize][point.x as usize];
+        self.map[point.y as usize][point.x as usize]
     }
 }
 
-fn get_area_of_inside(map: &Map) -> u32 {
+/// Returns the tiles of the loop and the tiles enclosed by it
+fn get_loop_and_inside(map: &Map) -> (HashSet<Point>, HashSet<Point>) {
     // Travel in both direction until you have visited all the nodes
     // find starting directions
     let (first_direction, second_direction) = get_starting_directions(map);
@@ -130,7 +151,7 @@ fn get_area_of_inside(map: &Map) -> u32 {
      
     let mut area : HashSet<Point> = HashSet::new();
     let mut traversed : HashSet<Point> = HashSet::new();
-    traversed.insert(map.starting_location.clone());
+    traversed.insert(map.starting_location);
 
     loop {
         let mut c_at_location = map.get(pointer);
@@ -157,29 +178,51 @@ fn get_area_of_inside(map: &Map) -> u32 {
             break;
         }
 
-        traversed.insert(pointer.clone());
+        traversed.insert(pointer);
     }
 
-    area.len() as u32
+    (boundary, area)
+}
+
+const CELL_SIZE: usize = 4;
+
+/// Draws the loop with the inside tiles shaded, for `aoc report`
+fn write_report_svg(map: &Map, boundary: &HashSet<Point>, area: &HashSet<Point>) -> std::io::Result<()> {
+    let width = map.map.iter().map(|line| line.len()).max().unwrap_or(0);
+
+    let mut file = File::create("report.svg")?;
+    writeln!(file, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">",
+        width * CELL_SIZE, map.map.len() * CELL_SIZE)?;
+    writeln!(file, "<rect width=\"100%\" height=\"100%\" fill=\"#0f0f23\"/>")?;
+
+    for (points, color) in [(boundary, "#ffff66"), (area, "#3399ff")] {
+        for point in points {
+            writeln!(file, "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                point.x as usize * CELL_SIZE, point.y as usize * CELL_SIZE, CELL_SIZE, CELL_SIZE, color)?;
+        }
+    }
+
+    writeln!(file, "</svg>")?;
+    Ok(())
 }
 
 fn flood_fill(starting_point: &Point, boundary: &HashSet<Point>) -> HashSet<Point> {
     let mut visited: HashSet<Point> = HashSet::new();
     let mut queue: VecDeque<Point> = VecDeque::new();
-    queue.push_back(starting_point.clone());
-    while queue.len() > 0 {
+    queue.push_back(*starting_point);
+    while !queue.is_empty() {
         let curr_point = queue.pop_front().expect("Should be a point still");
         if boundary.contains(&curr_point) {
             continue;
         }
 
         if !visited.contains(&curr_point) {
-            visited.insert(curr_point.clone());
+            visited.insert(curr_point);
 
-            queue.push_back(curr_point.clone() + &DOWN);
-            queue.push_back(curr_point.clone() + &UP);
-            queue.push_back(curr_point.clone() + &RIGHT);
-            queue.push_back(curr_point.clone() + &LEFT);
+            queue.push_back(curr_point + &DOWN);
+            queue.push_back(curr_point + &UP);
+            queue.push_back(curr_point + &RIGHT);
+            queue.push_back(curr_point + &LEFT);
         }
     }
 
@@ -215,8 +258,8 @@ fn calc_boundary_and_get_turns(map: &Map, first_point: &Point, first_direction:
     let mut first_pointer = first_point.to_owned();
     let mut first_direction = first_direction.to_owned();
 
-    boundary.insert(map.starting_location.clone());
-    boundary.insert(first_pointer.clone());
+    boundary.insert(map.starting_location);
+    boundary.insert(first_pointer);
 
     loop {
         let first_char = map.get(first_pointer);
@@ -235,7 +278,7 @@ fn calc_boundary_and_get_turns(map: &Map, first_point: &Point, first_direction:
             break;
         }
 
-        boundary.insert(first_pointer.clone());
+        boundary.insert(first_pointer);
     }
     (boundary, turns)
 }
@@ -246,8 +289,8 @@ fn get_starting_directions(map: &Map) -> (Direction, Direction) {
     DIRECTION_TO_MOVEMENT.keys().for_each(|direction| {
         let point = &DIRECTION_TO_MOVEMENT[direction];
 
-        let new_x = map.starting_location.x as i32 + point.x;
-        let new_y = map.starting_location.y as i32 + point.y;
+        let new_x = map.starting_location.x + point.x;
+        let new_y = map.starting_location.y + point.y;
 
         if new_x < 0 || new_y < 0 || new_x >= map.map[0].len() as i32 || new_y >= map.map.len() as i32 {
             return;
@@ -381,8 +424,26 @@ mod tests {
 
         let map: Map = Map::new(contents);
 
-        let area = get_area_of_inside(&map);
-        assert_eq!(1, area);
+        let (_, area) = get_loop_and_inside(&map);
+        assert_eq!(1, area.len());
+    }
+
+    #[test]
+    fn test_square_loop_tiles() {
+        let contents = String::from(".....\n.S-7.\n.|.|.\n.L-J.\n.....");
+
+        let map: Map = Map::new(contents);
+
+        let (boundary, area) = get_loop_and_inside(&map);
+        assert_eq!(8, boundary.len());
+        assert_eq!(HashSet::from([Point::new(2, 2)]), area);
+    }
+
+    #[test]
+    fn test_map_from_bytes() {
+        let contents = String::from(".....\n.S-7.\n.|.|.\n.L-J.\n.....\n");
+
+        assert_eq!(Map::from_bytes(contents.as_bytes()), Map::new(contents));
     }
 
     #[test]
@@ -1,24 +1,34 @@
-use std::{fs, fmt, time::SystemTime};
+use std::{collections::HashSet, env, fs::{self, File}, fmt, io::Write, time::SystemTime};
+
+use aoc::{alloc::measure, input};
 
 fn main() {
     let contents = fs::read_to_string("input.txt")
         .expect("Should have been able to read the file input.txt");
+    aoc::unwrap_or_exit(input::check_ascii(contents.as_bytes()));
+
+    if env::args().any(|arg| arg == "--compare-parsers") {
+        input::compare_parsers(&contents, 100, Universe::new, Universe::from_bytes);
+        return;
+    }
 
-    let mut universe = Universe::new(&contents);
+    let mut universe = measure("parse", || Universe::from_bytes(contents.as_bytes()));
 
     let now = SystemTime::now();
-    universe.expand_universe();
+    measure("expand", || universe.expand_universe());
     println!("{:?}", now.elapsed().expect("Should have elapsed"));
 
-    let points = universe.get_points();
+    let points = measure("part", || universe.get_points());
+    write_report_svg(&universe, &points).expect("Should have been able to write report.svg");
+
     println!("Answer: {}", sum_distances(&points));
 }
 
-fn sum_distances(points: &Vec<(i32, i32)>) -> u32 {
+fn sum_distances(points: &[(i32, i32)]) -> u32 {
     let mut sum = 0;
     for (i, val) in points.iter().enumerate() {
-        for j in i+1..points.len() {
-            sum += get_distance(val, &points[j]);
+        for other in &points[i+1..] {
+            sum += get_distance(val, other);
         }
     }
     sum
@@ -28,6 +38,37 @@ fn get_distance(p1: &(i32, i32), p2: &(i32, i32)) -> u32 {
     ((p1.0 - p2.0).abs() + (p1.1-p2.1).abs()) as u32
 }
 
+const CELL_SIZE: usize = 4;
+
+/// Draws the expanded universe for `aoc report`, shading the rows and columns without galaxies
+fn write_report_svg(universe: &Universe, points: &[(i32, i32)]) -> std::io::Result<()> {
+    let rows: HashSet<i32> = points.iter().map(|point| point.0).collect();
+    let columns: HashSet<i32> = points.iter().map(|point| point.1).collect();
+
+    let width = universe.width() * CELL_SIZE;
+    let height = universe.height() * CELL_SIZE;
+
+    let mut file = File::create("report.svg")?;
+    writeln!(file, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">", width, height)?;
+    writeln!(file, "<rect width=\"100%\" height=\"100%\" fill=\"#0f0f23\"/>")?;
+
+    for i in (0..universe.height() as i32).filter(|i| !rows.contains(i)) {
+        writeln!(file, "<rect x=\"0\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#1f1f43\"/>", i as usize * CELL_SIZE, width, CELL_SIZE)?;
+    }
+
+    for j in (0..universe.width() as i32).filter(|j| !columns.contains(j)) {
+        writeln!(file, "<rect x=\"{}\" y=\"0\" width=\"{}\" height=\"{}\" fill=\"#1f1f43\"/>", j as usize * CELL_SIZE, CELL_SIZE, height)?;
+    }
+
+    for (i, j) in points {
+        writeln!(file, "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"#ffff66\"/>",
+            *j as usize * CELL_SIZE + CELL_SIZE / 2, *i as usize * CELL_SIZE + CELL_SIZE / 2, CELL_SIZE / 2)?;
+    }
+
+    writeln!(file, "</svg>")?;
+    Ok(())
+}
+
 #[derive(Eq)]
 struct Universe {
     map: Vec<Vec<char>>,
@@ -46,6 +87,12 @@ impl Universe {
         Universe { map }
     }
 
+    /// Same as `Universe::new`, without decoding UTF-8
+    fn from_bytes(contents: &[u8]) -> Universe {
+        let map = input::lines(contents).map(|line| line.iter().map(|&b| b as char).collect()).collect();
+        Universe { map }
+    }
+
     fn expand_universe(&mut self) {
         self.expand_horizontally();
         self.expand_vertically();
@@ -148,6 +195,12 @@ mod tests {
         assert_eq!(galaxy, expanded_galaxy);
     }
 
+    #[test]
+    fn test_universe_from_bytes() {
+        let contents = "#..\n...\n..#\n";
+        assert_eq!(Universe::from_bytes(contents.as_bytes()), Universe::new(contents));
+    }
+
     #[test]
     fn test_get_points() {
         let mut galaxy = Universe::new("#..\n...\n..#");
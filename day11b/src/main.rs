@@ -1,10 +1,18 @@
-use std::{collections::HashMap, fs, time::SystemTime};
+use std::{collections::HashMap, env, fs, time::SystemTime};
+
+use aoc::input;
 
 fn main() {
     let contents =
         fs::read_to_string("input.txt").expect("Should have been able to read the file input.txt");
+    aoc::unwrap_or_exit(input::check_ascii(contents.as_bytes()));
+
+    if env::args().any(|arg| arg == "--compare-parsers") {
+        input::compare_parsers(&contents, 100, Universe::new, Universe::from_bytes);
+        return;
+    }
 
-    let universe = Universe::new(&contents);
+    let universe = Universe::from_bytes(contents.as_bytes());
     let points = universe.get_points();
 
     let now = SystemTime::now();
@@ -12,11 +20,11 @@ fn main() {
     println!("Time Elapsed: {:?}", now.elapsed().expect("Should have time"));
 }
 
-fn sum_distances(points: &Vec<(i64, i64)>) -> u64 {
+fn sum_distances(points: &[(i64, i64)]) -> u64 {
     let mut sum = 0;
     for (i, val) in points.iter().enumerate() {
-        for j in i + 1..points.len() {
-            sum += get_distance(val, &points[j]);
+        for other in &points[i + 1..] {
+            sum += get_distance(val, other);
         }
     }
     sum
@@ -26,6 +34,7 @@ fn get_distance(p1: &(i64, i64), p2: &(i64, i64)) -> u64 {
     ((p1.0 - p2.0).abs() + (p1.1 - p2.1).abs()) as u64
 }
 
+#[derive(PartialEq, Debug)]
 struct Universe {
     map: Vec<Vec<char>>,
 }
@@ -39,6 +48,14 @@ impl Universe {
         Universe { map }
     }
 
+    /// Same as `Universe::new`, without decoding UTF-8
+    fn from_bytes(contents: &[u8]) -> Universe {
+        let map = input::lines(contents)
+            .map(|line| line.iter().map(|&b| b as char).collect())
+            .collect();
+        Universe { map }
+    }
+
     fn get_points(&self) -> Vec<(i64, i64)> {
         let mut ret_vec: Vec<(i64, i64)> = Vec::new();
         let mut precomputed_horizontal: HashMap<usize, i64> = HashMap::new();
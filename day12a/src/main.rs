@@ -1,21 +1,90 @@
-use std::{fs, time::SystemTime};
+use std::{env, fs, str, time::SystemTime};
+
+use aoc::{alloc::measure, breakdown::{self, Breakdown, Value}, input::{self, StreamArgs}};
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    aoc::unwrap_or_exit(breakdown::check_not_streaming(&args));
+    if let Some(stream) = aoc::unwrap_or_exit(StreamArgs::from_args(&args, "input.txt")) {
+        let sum = stream.sum_lines(get_record_combinations_bytes)
+            .expect("Should have been able to read the input");
+        println!("Answer: {}", sum);
+        return;
+    }
+
     let contents = fs::read_to_string("input.txt")
         .expect("Should be able to read the file");
 
+    if args.iter().any(|arg| arg == "--compare-parsers") {
+        input::compare_parsers(&contents, 100, parse_records, parse_records_bytes);
+        return;
+    }
+
     let now = SystemTime::now();
-    let mut sum = 0;
+    let (records, combinations) = measure("part", || {
+        let records = parse_records_bytes(contents.as_bytes());
+        let combinations: Vec<u64> = records.iter()
+            .map(|&(combination_str, grouping_str)| get_possible_combinations(combination_str, grouping_str))
+            .collect();
+        (records, combinations)
+    });
+    let elapsed = now.elapsed().expect("Elapsed time should exist");
+
+    if let Some(path) = breakdown::path_from_args(&args) {
+        get_breakdown(contents.as_bytes(), &records, &combinations).write(path)
+            .expect("Should have been able to write the breakdown");
+        println!("Breakdown written to {}", path);
+    }
+
+    println!("Answer: {}", combinations.iter().sum::<u64>());
+    println!("Time Elapsed: {:?}", elapsed);
+}
+
+/// Splits each line into the springs and the grouping of damaged springs
+fn parse_records(contents: &str) -> Vec<(&str, &str)> {
+    let mut records = Vec::new();
     for line in contents.lines() {
         let mut iter = line.split(' ');
 
         let combination_str = iter.next().expect("Should exist");
         let grouping_str = iter.next().expect("Should exist");
-        
-        sum += get_possible_combinations(combination_str, grouping_str);
+
+        records.push((combination_str, grouping_str));
     }
-    println!("Answer: {}", sum);
-    println!("Time Elapsed: {:?}", now.elapsed().expect("Elapsed time should exist"));
+    records
+}
+
+/// Same as `parse_records`, for the input bytes
+fn parse_records_bytes(contents: &[u8]) -> Vec<(&str, &str)> {
+    input::lines(contents).map(parse_record_bytes).collect()
+}
+
+fn get_record_combinations_bytes(line: &[u8]) -> u64 {
+    let (combination_str, grouping_str) = parse_record_bytes(line);
+    get_possible_combinations(combination_str, grouping_str)
+}
+
+/// A row per line with the combinations the solve pass found for it
+fn get_breakdown(contents: &[u8], records: &[(&str, &str)], combinations: &[u64]) -> Breakdown {
+    let mut breakdown = Breakdown::new(&["springs", "groups", "unknown"]);
+    let rows = input::lines(contents).zip(records).zip(combinations);
+    for (i, ((line, &(combination_str, grouping_str)), combinations)) in rows.enumerate() {
+        breakdown.add(i + 1, line, combinations, vec![
+            Value::text(combination_str),
+            Value::text(grouping_str),
+            Value::number(combination_str.matches('?').count()),
+        ]);
+    }
+    breakdown
+}
+
+fn parse_record_bytes(line: &[u8]) -> (&str, &str) {
+    let (combination, grouping) = input::split_once(line, b' ').expect("Should exist");
+    (
+        str::from_utf8(combination).expect("Should be text"),
+        str::from_utf8(grouping).expect("Should be text"),
+    )
 }
 
 // The input is pretty short, we don't have to memoize it or whatever
@@ -73,6 +142,15 @@ mod tests {
         assert_eq!(get_possible_combinations(input, grouping_str), 3);
     }
 
+    #[test]
+    fn test_parse_records_bytes() {
+        let contents = "???.### 1,1,3\n.??..??...?##. 1,1,3\n";
+        let records = parse_records_bytes(contents.as_bytes());
+
+        assert_eq!(records, parse_records(contents));
+        assert_eq!(records[0], ("???.###", "1,1,3"));
+    }
+
     #[test]
     fn test_find_grouping() {
         let input = "#.###..##.";
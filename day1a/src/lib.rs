@@ -0,0 +1,392 @@
+//! Calibration engine shared by both parts of day 1.
+//!
+//! A line's calibration value is its first digit token followed by its last. Which tokens count is decided by a
+//! [`Vocabulary`]: day1a is [`Vocabulary::digits`], day1b is [`Vocabulary::english`], and either day can load
+//! another one with `--vocabulary <file>`.
+//!
+//! What happens to a line without any token is decided by a [`MissingDigits`] policy, set with
+//! `--missing-digits error|skip|zero`. Whatever the policy, [`Calibration`] keeps track of the affected lines so they
+//! can be reported instead of silently changing the total.
+
+pub mod scanner;
+pub mod vocabulary;
+
+use std::{fmt, str};
+
+use aoc::{breakdown::{Breakdown, Value}, input};
+use scanner::DigitScanner;
+use vocabulary::Vocabulary;
+
+pub struct Calibrator {
+    scanner: DigitScanner,
+}
+
+/// A token matched in a line: its text as it appears in the line, its byte offset and the digit it stands for
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match<'a> {
+    pub text: &'a str,
+    pub offset: usize,
+    pub digit: u32,
+}
+
+/// The first and last token of a line, which are the same match when the line has only one
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineMatches<'a> {
+    pub first: Match<'a>,
+    pub last: Match<'a>,
+}
+
+impl Calibrator {
+    pub fn new(vocabulary: &Vocabulary) -> Calibrator {
+        Calibrator {
+            scanner: DigitScanner::new(&vocabulary.tokens(), vocabulary.case_insensitive),
+        }
+    }
+
+    /// Returns the first and last digit of the line, or `None` if it has no tokens
+    pub fn digits(&self, line: &[u8]) -> Option<(u32, u32)> {
+        self.scanner.first_and_last(line)
+            .map(|(first, last)| (first.digit, last.digit))
+    }
+
+    /// Returns the first and last token of the line, or `None` if it has no tokens
+    pub fn matches<'a>(&self, line: &'a [u8]) -> Option<LineMatches<'a>> {
+        // Tokens are UTF-8 and only ASCII letters are folded, so a match always covers whole characters
+        let to_match = |token: scanner::Token| Match {
+            text: str::from_utf8(&line[token.start..token.start + token.len]).expect("Matched tokens should be UTF-8"),
+            offset: token.start,
+            digit: token.digit,
+        };
+
+        let (first, last) = self.scanner.first_and_last(line)?;
+        Some(LineMatches { first: to_match(first), last: to_match(last) })
+    }
+}
+
+/// Returns the first and last token of every line of `contents`, `None` for lines without any
+pub fn line_matches<'a>(calibrator: &Calibrator, contents: &'a [u8]) -> Vec<Option<LineMatches<'a>>> {
+    input::lines(contents).map(|line| calibrator.matches(line)).collect()
+}
+
+/// Renders every line with its matches highlighted, and the calibration value in front of it
+pub fn show_matches(calibrator: &Calibrator, contents: &[u8]) -> String {
+    let mut shown = String::new();
+    for line in input::lines(contents) {
+        match calibrator.matches(line) {
+            Some(matches) => shown.push_str(&format!("{:>4}  {}\n",
+                matches.first.digit * 10 + matches.last.digit, highlight(line, &matches))),
+            None => shown.push_str(&format!("   -  {}\n", String::from_utf8_lossy(line))),
+        }
+    }
+    shown
+}
+
+/// Returns the line with its first token in green and its last in blue, for showing matches in a terminal.
+/// When the tokens overlap, the overlapping part is shown as part of the first.
+pub fn highlight(line: &[u8], matches: &LineMatches) -> String {
+    const GREEN: &str = "\x1b[32m";
+    const BLUE: &str = "\x1b[34m";
+    const RESET: &str = "\x1b[0m";
+
+    let first_end = matches.first.offset + matches.first.text.len();
+    let last_start = matches.last.offset.max(first_end);
+    let last_end = matches.last.offset + matches.last.text.len();
+
+    let mut highlighted = String::new();
+    highlighted.push_str(&String::from_utf8_lossy(&line[..matches.first.offset]));
+    highlighted.push_str(GREEN);
+    highlighted.push_str(matches.first.text);
+    highlighted.push_str(RESET);
+    highlighted.push_str(&String::from_utf8_lossy(&line[first_end..last_start]));
+    if last_start < last_end {
+        highlighted.push_str(BLUE);
+        highlighted.push_str(&String::from_utf8_lossy(&line[last_start..last_end]));
+        highlighted.push_str(RESET);
+    }
+    highlighted.push_str(&String::from_utf8_lossy(&line[last_end..]));
+    highlighted
+}
+
+/// What to do with a line that has no digit tokens
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MissingDigits {
+    /// Stop with an error naming the line
+    Error,
+    /// Leave the line out, as if it wasn't in the input
+    Skip,
+    /// Count the line with a calibration value of 0
+    Zero,
+}
+
+impl MissingDigits {
+    pub fn parse(policy: &str) -> Result<MissingDigits, String> {
+        match policy {
+            "error" => Ok(MissingDigits::Error),
+            "skip" => Ok(MissingDigits::Skip),
+            "zero" => Ok(MissingDigits::Zero),
+            policy => Err(format!("Unknown policy for lines without digits: {}, expected error, skip or zero", policy)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct NoDigitsError {
+    /// 1-based line number
+    pub line: usize,
+    pub text: String,
+}
+
+impl fmt::Display for NoDigitsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Line {} has no digits: {}", self.line, self.text)
+    }
+}
+
+/// Running total of the calibration values, applying the policy line by line.
+/// Works the same for a whole file and for a stream.
+#[derive(Debug, PartialEq)]
+pub struct Calibration {
+    pub policy: MissingDigits,
+    pub sum: u64,
+    /// Number of lines added so far
+    pub lines: usize,
+    /// 1-based numbers of the lines without any token
+    pub missing: Vec<usize>,
+}
+
+impl Calibration {
+    pub fn new(policy: MissingDigits) -> Calibration {
+        Calibration { policy, sum: 0, lines: 0, missing: Vec::new() }
+    }
+
+    /// Adds the next line and returns its first and last digit.
+    /// A line without any is `None` when the policy skips or zeroes it, and an error otherwise.
+    pub fn add(&mut self, calibrator: &Calibrator, line: &[u8]) -> Result<Option<(u32, u32)>, NoDigitsError> {
+        self.lines += 1;
+
+        match calibrator.digits(line) {
+            Some((first_digit, last_digit)) => {
+                self.sum += u64::from(first_digit * 10 + last_digit);
+                Ok(Some((first_digit, last_digit)))
+            },
+            None if self.policy == MissingDigits::Error => Err(NoDigitsError {
+                line: self.lines,
+                text: String::from_utf8_lossy(line).to_string(),
+            }),
+            None => {
+                self.missing.push(self.lines);
+                Ok(None)
+            },
+        }
+    }
+
+    /// Describes the lines without digits, or `None` if every line had some
+    pub fn summary(&self) -> Option<String> {
+        const MAX_LISTED: usize = 20;
+
+        if self.missing.is_empty() {
+            return None;
+        }
+
+        let action = match self.policy {
+            MissingDigits::Skip => "skipped",
+            MissingDigits::Zero => "counted as 0",
+            MissingDigits::Error => "reported",
+        };
+        let mut listed: Vec<String> = self.missing.iter().take(MAX_LISTED).map(|line| line.to_string()).collect();
+        if self.missing.len() > MAX_LISTED {
+            listed.push(format!("and {} more", self.missing.len() - MAX_LISTED));
+        }
+
+        Some(format!("{} of {} lines had no digits and were {}: {}", self.missing.len(), self.lines, action, listed.join(", ")))
+    }
+}
+
+/// Adds up every line of `contents`
+pub fn calibrate(calibrator: &Calibrator, contents: &[u8], policy: MissingDigits) -> Result<Calibration, NoDigitsError> {
+    let mut calibration = Calibration::new(policy);
+    for line in input::lines(contents) {
+        calibration.add(calibrator, line)?;
+    }
+    Ok(calibration)
+}
+
+/// A row per line with its first and last digit. Lines without digits are left out when they are skipped.
+pub fn get_breakdown(calibrator: &Calibrator, contents: &[u8], policy: MissingDigits) -> Result<Breakdown, NoDigitsError> {
+    let mut breakdown = Breakdown::new(&["first_digit", "last_digit"]);
+    let mut calibration = Calibration::new(policy);
+    for (i, line) in input::lines(contents).enumerate() {
+        match calibration.add(calibrator, line)? {
+            Some((first_digit, last_digit)) => breakdown.add(i + 1, line, first_digit * 10 + last_digit,
+                vec![Value::number(first_digit), Value::number(last_digit)]),
+            None if policy == MissingDigits::Zero => breakdown.add(i + 1, line, 0,
+                vec![Value::text(""), Value::text("")]),
+            None => {},
+        }
+    }
+    Ok(breakdown)
+}
+
+/// Reads the policy given with `--missing-digits <policy>`, or returns `default` if there is none
+pub fn policy_from_args(args: &[String], default: MissingDigits) -> Result<MissingDigits, String> {
+    match args.iter().position(|arg| arg == "--missing-digits") {
+        Some(position) => MissingDigits::parse(args.get(position + 1).ok_or(String::from("--missing-digits needs a policy"))?),
+        None => Ok(default),
+    }
+}
+
+/// Loads the vocabulary given with `--vocabulary <file>`, or returns `default` if there is none
+pub fn vocabulary_from_args(args: &[String], default: Vocabulary) -> Result<Vocabulary, String> {
+    match args.iter().position(|arg| arg == "--vocabulary") {
+        Some(position) => {
+            let path = args.get(position + 1).ok_or(String::from("--vocabulary needs a file"))?;
+            Vocabulary::from_file(path)
+        },
+        None => Ok(default),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn day1a_and_day1b_are_configurations() {
+        let line = b"xtwone3four";
+        assert_eq!(Calibrator::new(&Vocabulary::digits()).digits(line), Some((3, 3)));
+        assert_eq!(Calibrator::new(&Vocabulary::english()).digits(line), Some((2, 4)));
+    }
+
+    #[test]
+    fn loaded_vocabularies_compose_with_digits() {
+        let roman = Vocabulary::parse("digits\ncase-insensitive\ni = 1\nii = 2\niii = 3\niv = 4\nv = 5").expect("Test - parse");
+        let calibrator = Calibrator::new(&roman);
+
+        assert_eq!(calibrator.digits(b"xIVa7"), Some((4, 7)));
+        assert_eq!(calibrator.digits(b"8-iii"), Some((8, 3)));
+        assert_eq!(calibrator.digits(b"none"), None);
+    }
+
+    #[test]
+    fn shipped_vocabularies_load() {
+        for name in ["german", "roman", "english-zero"] {
+            let path = format!("{}/vocabularies/{}.txt", env!("CARGO_MANIFEST_DIR"), name);
+            Vocabulary::from_file(&path).expect("Test - load vocabulary");
+        }
+
+        let german = Vocabulary::from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/vocabularies/german.txt")).expect("Test - load vocabulary");
+        assert_eq!(Calibrator::new(&german).digits("xFünfzigacht".as_bytes()), Some((5, 8)));
+    }
+
+    #[test]
+    fn policies_for_lines_without_digits() {
+        let calibrator = Calibrator::new(&Vocabulary::digits());
+        let contents = b"a1b2\nnothing\n3\n\n";
+
+        let error = calibrate(&calibrator, contents, MissingDigits::Error);
+        assert_eq!(error, Err(NoDigitsError { line: 2, text: String::from("nothing") }));
+
+        let zero = calibrate(&calibrator, contents, MissingDigits::Zero).expect("Test - zero");
+        assert_eq!((zero.sum, zero.lines, zero.missing.clone()), (12 + 33, 4, vec![2, 4]));
+        assert_eq!(zero.summary(), Some(String::from("2 of 4 lines had no digits and were counted as 0: 2, 4")));
+
+        let skip = calibrate(&calibrator, contents, MissingDigits::Skip).expect("Test - skip");
+        assert_eq!(skip.sum, zero.sum);
+        assert_eq!(skip.summary(), Some(String::from("2 of 4 lines had no digits and were skipped: 2, 4")));
+
+        let all_good = calibrate(&calibrator, b"1\n2", MissingDigits::Error).expect("Test - all good");
+        assert_eq!(all_good.summary(), None);
+    }
+
+    #[test]
+    fn summary_is_truncated() {
+        let calibrator = Calibrator::new(&Vocabulary::digits());
+        let calibration = calibrate(&calibrator, "x\n".repeat(25).as_bytes(), MissingDigits::Skip).expect("Test - skip");
+        let summary = calibration.summary().expect("Test - summary");
+        assert!(summary.starts_with("25 of 25 lines"));
+        assert!(summary.ends_with("19, 20, and 5 more"));
+    }
+
+    #[test]
+    fn breakdown_follows_policy() {
+        let calibrator = Calibrator::new(&Vocabulary::digits());
+        let contents = b"a1b2\nnothing\n";
+
+        let zero = get_breakdown(&calibrator, contents, MissingDigits::Zero).expect("Test - zero").to_csv();
+        assert_eq!(zero, "line,raw,contribution,first_digit,last_digit\n1,a1b2,12,1,2\n2,nothing,0,,\n");
+
+        let skip = get_breakdown(&calibrator, contents, MissingDigits::Skip).expect("Test - skip").to_csv();
+        assert_eq!(skip, "line,raw,contribution,first_digit,last_digit\n1,a1b2,12,1,2\n");
+
+        assert!(get_breakdown(&calibrator, contents, MissingDigits::Error).is_err());
+    }
+
+    #[test]
+    fn parse_policy() {
+        assert_eq!(MissingDigits::parse("skip"), Ok(MissingDigits::Skip));
+        assert!(MissingDigits::parse("ignore").is_err());
+    }
+
+    fn token(text: &str, offset: usize, digit: u32) -> Match<'_> {
+        Match { text, offset, digit }
+    }
+
+    #[test]
+    fn matches_give_text_offset_and_digit() {
+        let calibrator = Calibrator::new(&Vocabulary::english());
+
+        let matches = calibrator.matches(b"oneight").expect("Test - matches");
+        assert_eq!(matches.first, token("one", 0, 1));
+        assert_eq!(matches.last, token("eight", 2, 8));
+
+        let matches = calibrator.matches(b"x7seven").expect("Test - matches");
+        assert_eq!(matches.first, token("7", 1, 7));
+        assert_eq!(matches.last, token("seven", 2, 7));
+
+        let matches = calibrator.matches("ñtwo".as_bytes()).expect("Test - matches");
+        assert_eq!(matches.first, token("two", 2, 2));
+        assert_eq!(matches.first, matches.last);
+
+        assert_eq!(calibrator.matches(b"abc"), None);
+    }
+
+    #[test]
+    fn matches_keep_the_case_of_the_line() {
+        let vocabulary = Vocabulary::parse("case-insensitive\nenglish").expect("Test - parse");
+        let matches = Calibrator::new(&vocabulary).matches(b"SEVENine").expect("Test - matches");
+        assert_eq!(matches.first, token("SEVEN", 0, 7));
+        assert_eq!(matches.last, token("Nine", 4, 9));
+    }
+
+    #[test]
+    fn line_matches_has_an_entry_per_line() {
+        let calibrator = Calibrator::new(&Vocabulary::digits());
+        let matches = line_matches(&calibrator, b"a1\nnone\n23\n");
+        assert_eq!(matches.len(), 3);
+        assert_eq!(matches[1], None);
+        assert_eq!(matches[2].as_ref().map(|m| (m.first.offset, m.last.offset)), Some((0, 1)));
+    }
+
+    #[test]
+    fn show_matches_has_a_line_per_line() {
+        let calibrator = Calibrator::new(&Vocabulary::digits());
+        assert_eq!(show_matches(&calibrator, b"a1\nnone\n"), "  11  a\x1b[32m1\x1b[0m\n   -  none\n");
+    }
+
+    #[test]
+    fn highlight_marks_first_and_last() {
+        let calibrator = Calibrator::new(&Vocabulary::english());
+
+        let line = b"xtwo3fourx";
+        let matches = calibrator.matches(line).expect("Test - matches");
+        assert_eq!(highlight(line, &matches), "x\x1b[32mtwo\x1b[0m3\x1b[34mfour\x1b[0mx");
+
+        let line = b"twone";
+        let matches = calibrator.matches(line).expect("Test - matches");
+        assert_eq!(highlight(line, &matches), "\x1b[32mtwo\x1b[0m\x1b[34mne\x1b[0m");
+
+        let line = b"a5b";
+        let matches = calibrator.matches(line).expect("Test - matches");
+        assert_eq!(highlight(line, &matches), "a\x1b[32m5\x1b[0mb");
+    }
+}
@@ -1,16 +1,60 @@
-use std::fs;
+use std::{env, fs};
+
+use aoc::{breakdown, input::{self, StreamArgs}};
+use day1::{Calibration, Calibrator, MissingDigits, vocabulary::Vocabulary};
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
     let file_path = "input.txt";
+
+    let vocabulary = aoc::unwrap_or_exit(day1::vocabulary_from_args(&args, Vocabulary::digits()));
+    let policy = aoc::unwrap_or_exit(day1::policy_from_args(&args, MissingDigits::Error));
+    let calibrator = Calibrator::new(&vocabulary);
+
+    aoc::unwrap_or_exit(breakdown::check_not_streaming(&args));
+    if let Some(stream) = aoc::unwrap_or_exit(StreamArgs::from_args(&args, file_path)) {
+        let mut calibration = Calibration::new(policy);
+        let sum = stream.sum_lines(|line| {
+            let digits = aoc::unwrap_or_exit(calibration.add(&calibrator, line));
+            digits.map_or(0, |(first_digit, last_digit)| u64::from(first_digit * 10 + last_digit))
+        })
+            .expect("Should have been able to read the input");
+
+        if let Some(summary) = calibration.summary() {
+            println!("{}", summary);
+        }
+        println!("Answer: {sum}");
+        return;
+    }
+
     let contents = fs::read_to_string(file_path)
         .expect("Should have been able to read the file");
 
-    let sum = sum_digits(contents); 
+    if args.iter().any(|arg| arg == "--compare-parsers") {
+        input::compare_parsers(&contents, 100, |contents| u64::from(sum_digits(contents.to_string())), |contents| sum_digits_bytes(&calibrator, contents));
+        return;
+    }
+
+    if args.iter().any(|arg| arg == "--show-matches") {
+        print!("{}", day1::show_matches(&calibrator, contents.as_bytes()));
+        return;
+    }
+
+    if let Some(path) = breakdown::path_from_args(&args) {
+        aoc::unwrap_or_exit(day1::get_breakdown(&calibrator, contents.as_bytes(), policy)).write(path)
+            .expect("Should have been able to write the breakdown");
+        println!("Breakdown written to {}", path);
+    }
+
+    let calibration = aoc::unwrap_or_exit(day1::calibrate(&calibrator, contents.as_bytes(), policy));
+    if let Some(summary) = calibration.summary() {
+        println!("{}", summary);
+    }
 
-    println!("Answer: {sum}");
+    println!("Answer: {}", calibration.sum);
 }
 
-fn sum_digits(contents: String) -> i32 {
+fn sum_digits(contents: String) -> u32 {
     let mut sum = 0;
     for line in contents.lines() {
         let mut forward_iter = line.chars();
@@ -20,10 +64,17 @@ fn sum_digits(contents: String) -> i32 {
         let sec_char = reverse_iter.find(|&c| c.is_ascii_digit());
         
         let digits = format!("{}{}", first_char.unwrap(), sec_char.unwrap());
-        sum += digits.parse::<i32>().unwrap();
+        sum += digits.parse::<u32>().unwrap();
     }
 
-    return sum;
+    sum
+}
+
+/// Same as `sum_digits`, reading the digits straight out of the input bytes instead of formatting and parsing them
+fn sum_digits_bytes(calibrator: &Calibrator, contents: &[u8]) -> u64 {
+    day1::calibrate(calibrator, contents, MissingDigits::Error)
+        .expect("Line should have a digit")
+        .sum
 }
 
 #[cfg(test)]
@@ -36,4 +87,31 @@ mod tests {
         let sum = sum_digits(contents);
         assert_eq!(sum, 32 + 38 + 22);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn it_sums_bytes_correctly() {
+        let contents = "a322g\nb3da34fas8sadfasd\nasdfa2sdf22\n";
+        let calibrator = Calibrator::new(&Vocabulary::digits());
+        assert_eq!(sum_digits_bytes(&calibrator, contents.as_bytes()), 32 + 38 + 22);
+    }
+
+    #[test]
+    fn breakdown_has_a_row_per_line() {
+        let calibrator = Calibrator::new(&Vocabulary::digits());
+        let csv = day1::get_breakdown(&calibrator, b"a322g\nb3da34fas8sadfasd\n", MissingDigits::Error).expect("Test - breakdown").to_csv();
+        assert_eq!(csv, "line,raw,contribution,first_digit,last_digit\n1,a322g,32,3,2\n2,b3da34fas8sadfasd,38,3,8\n");
+    }
+
+    #[test]
+    fn it_sums_streamed_lines_correctly() {
+        let contents = "a322g\r\nb3da34fas8sadfasd\r\nasdfa2sdf22";
+        let calibrator = Calibrator::new(&Vocabulary::digits());
+        let mut calibration = Calibration::new(MissingDigits::Error);
+        let sum = input::sum_lines(contents.as_bytes(), None, |line| {
+            let (first_digit, last_digit) = calibration.add(&calibrator, line).expect("Test - digits").expect("Test - digits");
+            u64::from(first_digit * 10 + last_digit)
+        }).expect("Test - read");
+        assert_eq!(sum, 32 + 38 + 22);
+        assert_eq!(calibration.sum, sum);
+    }
+}
@@ -0,0 +1,193 @@
+//! Aho-Corasick automaton that finds the first and last digit token of a line in one pass.
+//!
+//! The automaton works on bytes, so tokens are matched as UTF-8 byte sequences and a line with non-ASCII text
+//! can never produce a match that starts or ends in the middle of a character. Every token that ends at a
+//! position is found, so overlapping words like "twone" or "eightwo" give both of their digits.
+//!
+//! Case-insensitive scanners fold ASCII letters only, which keeps every match the same length in bytes as the line.
+
+/// A token found in a line. `start` and `len` are in bytes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Token {
+    pub start: usize,
+    pub len: usize,
+    pub digit: u32,
+}
+
+impl Token {
+    fn end(&self) -> usize {
+        self.start + self.len
+    }
+}
+
+pub struct DigitScanner {
+    /// Full transition table, `transitions[state][byte]` is the next state, for bytes passed through `fold`
+    transitions: Vec<[u32; 256]>,
+    fold: [u8; 256],
+    /// Longest token ending in each state, as its length and digit
+    outputs: Vec<Option<(usize, u32)>>,
+}
+
+impl DigitScanner {
+    /// Builds the automaton for `tokens`, each a word and the digit it stands for
+    pub fn new(tokens: &[(&str, u32)], case_insensitive: bool) -> DigitScanner {
+        let mut fold = [0; 256];
+        for (i, folded) in fold.iter_mut().enumerate() {
+            *folded = if case_insensitive { (i as u8).to_ascii_lowercase() } else { i as u8 };
+        }
+
+        // Build the trie first, u32::MAX marks a missing child
+        let mut transitions = vec![[u32::MAX; 256]];
+        let mut outputs = vec![None];
+        for (word, digit) in tokens {
+            assert!(!word.is_empty(), "Tokens should not be empty");
+
+            let mut state = 0;
+            for &b in word.as_bytes() {
+                let b = fold[b as usize];
+                if transitions[state][b as usize] == u32::MAX {
+                    transitions.push([u32::MAX; 256]);
+                    outputs.push(None);
+                    transitions[state][b as usize] = (transitions.len() - 1) as u32;
+                }
+                state = transitions[state][b as usize] as usize;
+            }
+
+            // The first word wins if the same word is given twice
+            if outputs[state].is_none() {
+                outputs[state] = Some((word.len(), *digit));
+            }
+        }
+
+        // Then fill in the missing transitions breadth first, following the failure links
+        let mut failure = vec![0; transitions.len()];
+        let mut queue = std::collections::VecDeque::new();
+        for next in transitions[0].iter_mut() {
+            match *next {
+                u32::MAX => *next = 0,
+                child => queue.push_back(child as usize),
+            }
+        }
+
+        while let Some(state) = queue.pop_front() {
+            let fallbacks = transitions[failure[state]];
+            for (next, fallback) in transitions[state].iter_mut().zip(fallbacks) {
+                if *next == u32::MAX {
+                    *next = fallback;
+                    continue;
+                }
+
+                let child = *next as usize;
+                failure[child] = fallback as usize;
+                // A child's own word is always longer than anything reachable through its failure link
+                if outputs[child].is_none() {
+                    outputs[child] = outputs[fallback as usize];
+                }
+                queue.push_back(child);
+            }
+        }
+
+        DigitScanner { transitions, fold, outputs }
+    }
+
+    /// Returns the token that starts first and the token that ends last, preferring the longer token on a tie.
+    /// They are the same token when the line only has one.
+    pub fn first_and_last(&self, line: &[u8]) -> Option<(Token, Token)> {
+        let mut first: Option<Token> = None;
+        let mut last: Option<Token> = None;
+
+        let mut state = 0;
+        for (i, &b) in line.iter().enumerate() {
+            state = self.transitions[state][self.fold[b as usize] as usize] as usize;
+
+            // Only the longest token ending here matters, the shorter ones start later and end at the same place
+            if let Some((len, digit)) = self.outputs[state] {
+                let token = Token { start: i + 1 - len, len, digit };
+                if first.is_none_or(|first| token.start < first.start || (token.start == first.start && token.len > first.len)) {
+                    first = Some(token);
+                }
+                if last.is_none_or(|last| token.end() > last.end()) {
+                    last = Some(token);
+                }
+            }
+        }
+
+        Some((first?, last?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vocabulary::Vocabulary;
+
+    fn english() -> DigitScanner {
+        DigitScanner::new(&Vocabulary::english().tokens(), false)
+    }
+
+    fn digits(line: &str) -> Option<(u32, u32)> {
+        english().first_and_last(line.as_bytes())
+            .map(|(first, last)| (first.digit, last.digit))
+    }
+
+    #[test]
+    fn finds_overlapping_words() {
+        assert_eq!(digits("twone"), Some((2, 1)));
+        assert_eq!(digits("eightwo"), Some((8, 2)));
+        assert_eq!(digits("oneight"), Some((1, 8)));
+        assert_eq!(digits("xtwone3four"), Some((2, 4)));
+        assert_eq!(digits("sevenine"), Some((7, 9)));
+    }
+
+    #[test]
+    fn recovers_after_partial_matches() {
+        assert_eq!(digits("ninine"), Some((9, 9)));
+        assert_eq!(digits("fofive"), Some((5, 5)));
+        assert_eq!(digits("sevsevenn"), Some((7, 7)));
+    }
+
+    #[test]
+    fn single_token_is_first_and_last() {
+        let (first, last) = english().first_and_last(b"abcthreexyz").expect("Test - token");
+        assert_eq!(first, Token { start: 3, len: 5, digit: 3 });
+        assert_eq!(first, last);
+    }
+
+    #[test]
+    fn handles_utf8() {
+        assert_eq!(digits("éone€ñ7ü"), Some((1, 7)));
+        assert_eq!(digits("😀two😀"), Some((2, 2)));
+
+        let (first, _) = english().first_and_last("ééfive".as_bytes()).expect("Test - token");
+        assert_eq!(first.start, 4);
+    }
+
+    #[test]
+    fn no_tokens() {
+        assert_eq!(digits(""), None);
+        assert_eq!(digits("abcdef"), None);
+        assert_eq!(digits("onx twx"), None);
+    }
+
+    #[test]
+    fn prefers_longer_tokens_starting_together() {
+        let scanner = DigitScanner::new(&[("i", 1), ("iv", 4), ("v", 5)], false);
+        let (first, last) = scanner.first_and_last(b"xivx").expect("Test - token");
+        assert_eq!(first.digit, 4);
+        assert_eq!(last.digit, 4);
+
+        let (_, last) = scanner.first_and_last(b"xivi").expect("Test - token");
+        assert_eq!(last.digit, 1);
+    }
+
+    #[test]
+    fn folds_ascii_case() {
+        let scanner = DigitScanner::new(&[("Eins", 1), ("zwei", 2)], true);
+        let (first, last) = scanner.first_and_last(b"xEINSxZwei").expect("Test - token");
+        assert_eq!((first.digit, last.digit), (1, 2));
+        assert_eq!(last.start, 6);
+
+        let scanner = DigitScanner::new(&[("eins", 1)], false);
+        assert_eq!(scanner.first_and_last(b"EINS"), None);
+    }
+}
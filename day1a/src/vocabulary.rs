@@ -0,0 +1,137 @@
+//! The tokens a calibration line is scanned for.
+//!
+//! A vocabulary file has one entry per line, blank lines and `#` comments are ignored:
+//!
+//! ```text
+//! # German, on top of the ASCII digits
+//! digits
+//! case-insensitive
+//! eins = 1
+//! zwei = 2
+//! ```
+//!
+//! `digits` adds the ASCII digits 0 to 9, `english` adds the words "one" to "nine" and `case-insensitive` makes
+//! ASCII letters match in either case. Every other line is a word and the digit (0 to 9) it stands for.
+
+use std::fs;
+
+pub const ENGLISH_WORDS: [&str; 9] = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vocabulary {
+    /// Whether the ASCII digits 0 to 9 are tokens
+    pub ascii_digits: bool,
+    pub case_insensitive: bool,
+    pub words: Vec<(String, u32)>,
+}
+
+impl Vocabulary {
+    /// Only the ASCII digits, day1a's rule
+    pub fn digits() -> Vocabulary {
+        Vocabulary { ascii_digits: true, case_insensitive: false, words: Vec::new() }
+    }
+
+    /// The ASCII digits and the English words "one" to "nine", day1b's rule
+    pub fn english() -> Vocabulary {
+        let mut vocabulary = Vocabulary::digits();
+        vocabulary.add_english();
+        vocabulary
+    }
+
+    fn add_english(&mut self) {
+        for (i, word) in ENGLISH_WORDS.iter().enumerate() {
+            self.words.push((word.to_string(), i as u32 + 1));
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Vocabulary, String> {
+        let mut vocabulary = Vocabulary { ascii_digits: false, case_insensitive: false, words: Vec::new() };
+
+        for (i, line) in text.lines().enumerate() {
+            let line = match line.split_once('#') {
+                Some((before, _)) => before.trim(),
+                None => line.trim(),
+            };
+
+            match line {
+                "" => continue,
+                "digits" => vocabulary.ascii_digits = true,
+                "english" => vocabulary.add_english(),
+                "case-insensitive" => vocabulary.case_insensitive = true,
+                entry => {
+                    let (word, digit) = entry.split_once('=')
+                        .ok_or(format!("Line {}: expected `word = digit`, found `{}`", i + 1, entry))?;
+                    let (word, digit) = (word.trim(), digit.trim());
+
+                    if word.is_empty() {
+                        return Err(format!("Line {}: the word is empty", i + 1));
+                    }
+                    let digit = digit.parse::<u32>().ok()
+                        .filter(|digit| *digit <= 9)
+                        .ok_or(format!("Line {}: `{}` is not a digit from 0 to 9", i + 1, digit))?;
+
+                    vocabulary.words.push((word.to_string(), digit));
+                },
+            }
+        }
+
+        if !vocabulary.ascii_digits && vocabulary.words.is_empty() {
+            return Err(String::from("The vocabulary has no tokens"));
+        }
+
+        Ok(vocabulary)
+    }
+
+    pub fn from_file(path: &str) -> Result<Vocabulary, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read vocabulary {}: {}", path, e))?;
+        Vocabulary::parse(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Every token with its digit, the ASCII digits first
+    pub fn tokens(&self) -> Vec<(&str, u32)> {
+        const ASCII_DIGITS: [&str; 10] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+
+        let mut tokens = Vec::new();
+        if self.ascii_digits {
+            for (digit, token) in ASCII_DIGITS.iter().enumerate() {
+                tokens.push((*token, digit as u32));
+            }
+        }
+        for (word, digit) in &self.words {
+            tokens.push((word.as_str(), *digit));
+        }
+        tokens
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_directives_and_words() {
+        let vocabulary = Vocabulary::parse("# German\ndigits\ncase-insensitive\n\neins = 1\n  zwei=2  # two\nnull = 0\n")
+            .expect("Test - parse");
+
+        assert!(vocabulary.ascii_digits);
+        assert!(vocabulary.case_insensitive);
+        assert_eq!(vocabulary.words, vec![
+            (String::from("eins"), 1), (String::from("zwei"), 2), (String::from("null"), 0),
+        ]);
+        assert_eq!(vocabulary.tokens().len(), 13);
+    }
+
+    #[test]
+    fn english_directive_matches_builtin() {
+        assert_eq!(Vocabulary::parse("digits\nenglish").expect("Test - parse"), Vocabulary::english());
+    }
+
+    #[test]
+    fn parse_reports_bad_lines() {
+        assert_eq!(Vocabulary::parse("digits\neins 1"), Err(String::from("Line 2: expected `word = digit`, found `eins 1`")));
+        assert_eq!(Vocabulary::parse("ten = 10"), Err(String::from("Line 1: `10` is not a digit from 0 to 9")));
+        assert_eq!(Vocabulary::parse(" = 1"), Err(String::from("Line 1: the word is empty")));
+        assert_eq!(Vocabulary::parse("# nothing"), Err(String::from("The vocabulary has no tokens")));
+    }
+}
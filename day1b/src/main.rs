@@ -1,16 +1,64 @@
-use std::{fs};
+use std::{env, fs};
 
-// Initialize array of strings for one, two, three ... nine
-const DIGITS: [&str; 9] = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+use aoc::{breakdown, input::{self, StreamArgs}};
+use day1::{Calibration, Calibrator, MissingDigits, vocabulary::{Vocabulary, ENGLISH_WORDS as DIGITS}};
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
     let file_path = "input.txt";
+
+    let vocabulary = aoc::unwrap_or_exit(day1::vocabulary_from_args(&args, Vocabulary::english()));
+    let policy = aoc::unwrap_or_exit(day1::policy_from_args(&args, MissingDigits::Zero));
+    let calibrator = Calibrator::new(&vocabulary);
+
+    aoc::unwrap_or_exit(breakdown::check_not_streaming(&args));
+    if let Some(stream) = aoc::unwrap_or_exit(StreamArgs::from_args(&args, file_path)) {
+        let mut calibration = Calibration::new(policy);
+        let sum = stream.sum_lines(|line| {
+            let digits = aoc::unwrap_or_exit(calibration.add(&calibrator, line));
+            digits.map_or(0, |(first_digit, last_digit)| u64::from(first_digit * 10 + last_digit))
+        })
+            .expect("Should have been able to read the input");
+
+        if let Some(summary) = calibration.summary() {
+            println!("{}", summary);
+        }
+        println!("Answer: {sum}");
+        return;
+    }
+
     let contents = fs::read_to_string(file_path)
         .expect("Should have been able to read the file");
 
-    let sum = sum_digits(contents); 
+    if args.iter().any(|arg| arg == "--compare-parsers") {
+        input::compare_parsers(&contents, 100, |contents| u64::from(sum_digits(contents.to_string())), |contents| sum_digits_bytes(&calibrator, contents));
+        return;
+    }
+
+    if args.iter().any(|arg| arg == "--compare-scanners") {
+        input::compare_implementations(contents.as_str(), 100,
+            ("sum_digits", |contents| u64::from(sum_digits(contents.to_string()))),
+            ("automaton", |contents| sum_digits_bytes(&calibrator, contents.as_bytes())));
+        return;
+    }
+
+    if args.iter().any(|arg| arg == "--show-matches") {
+        print!("{}", day1::show_matches(&calibrator, contents.as_bytes()));
+        return;
+    }
+
+    if let Some(path) = breakdown::path_from_args(&args) {
+        aoc::unwrap_or_exit(day1::get_breakdown(&calibrator, contents.as_bytes(), policy)).write(path)
+            .expect("Should have been able to write the breakdown");
+        println!("Breakdown written to {}", path);
+    }
+
+    let calibration = aoc::unwrap_or_exit(day1::calibrate(&calibrator, contents.as_bytes(), policy));
+    if let Some(summary) = calibration.summary() {
+        println!("{}", summary);
+    }
 
-    println!("Answer: {sum}");
+    println!("Answer: {}", calibration.sum);
 }
 
 fn sum_digits(contents: String) -> u32 {
@@ -71,7 +119,14 @@ fn sum_digits(contents: String) -> u32 {
         sum += first_char * 10 + second_char;
     }
 
-    return sum;
+    sum
+}
+
+/// Same as `sum_digits`, finding the digits of each line in a single pass with `calibrator`
+fn sum_digits_bytes(calibrator: &Calibrator, contents: &[u8]) -> u64 {
+    day1::calibrate(calibrator, contents, MissingDigits::Zero)
+        .expect("Lines without digits should count as 0")
+        .sum
 }
 
 #[cfg(test)]
@@ -91,4 +146,45 @@ mod tests {
         let sum = sum_digits(contents);
         assert_eq!(sum, 12);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn it_sums_bytes_correctly() {
+        let contents = "a322g\nb3da34fas8sadfasd\nasdfa2sdf22\nonetwo\nxtwone3four\n7pqrstsixteen\n";
+        let calibrator = Calibrator::new(&Vocabulary::english());
+        assert_eq!(sum_digits_bytes(&calibrator, contents.as_bytes()), u64::from(sum_digits(contents.to_string())));
+        assert_eq!(sum_digits_bytes(&calibrator, contents.as_bytes()), 32 + 38 + 22 + 12 + 24 + 76);
+    }
+
+    #[test]
+    fn automaton_matches_sum_digits() {
+        let calibrator = Calibrator::new(&Vocabulary::english());
+        let lines = [
+            "", "abc", "twone", "eightwo", "oneight", "nineight7sevenine", "threeeight", "fiveeeee",
+            "0zero", "ononeone", "sixsevenseve", "4nineeightseven2", "zoneight234", "tttwoo",
+        ];
+        for line in lines {
+            let (first_digit, last_digit) = calibrator.digits(line.as_bytes()).unwrap_or((0, 0));
+            assert_eq!(first_digit * 10 + last_digit, sum_digits(line.to_string()), "line {:?}", line);
+        }
+
+        // sum_digits slices lines by char index, so it only handles ASCII
+        assert_eq!(calibrator.digits("éone€ñ7ü".as_bytes()), Some((1, 7)));
+    }
+
+    #[test]
+    fn it_matches_overlapping_words() {
+        let calibrator = Calibrator::new(&Vocabulary::english());
+        let expected = [
+            ("oneight", ("one", 0), ("eight", 2)),
+            ("twone", ("two", 0), ("one", 2)),
+            ("eightwothree", ("eight", 0), ("three", 7)),
+            ("7pqrstsixteen", ("7", 0), ("six", 6)),
+        ];
+
+        for (line, first, last) in expected {
+            let matches = calibrator.matches(line.as_bytes()).expect("Test - matches");
+            assert_eq!((matches.first.text, matches.first.offset), first, "line {:?}", line);
+            assert_eq!((matches.last.text, matches.last.offset), last, "line {:?}", line);
+        }
+    }
+}
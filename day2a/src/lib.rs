@@ -0,0 +1,418 @@
+//! Cube game model shared by both parts of day 2.
+//!
+//! A line such as `Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red` is parsed into a [`Game`] whose rounds are
+//! [`CubeSet`]s, and both puzzle answers are queries over the parsed games. `Display` writes a game back in the
+//! same format, so `game.to_string().parse::<Game>()` gives the same game.
+//!
+//! Colors are not limited to red, green and blue: any name is accepted, and a bag can be given with
+//! `--bag red=12,green=13,blue=14,yellow=5` or `--bag-file <file>`. Whether a game is possible looks at every color
+//! it draws, and one the bag doesn't have makes it impossible. The power of a game multiplies the most cubes it shows
+//! of each of its own colors, so it doesn't depend on the bag or on the other games and streams the same way.
+
+pub mod likelihood;
+pub mod stats;
+
+use std::{fmt, fs, str::{self, FromStr}};
+
+use aoc::input;
+
+/// Number of cubes of each color. Colors keep the order they were added in, so a round prints the way it was written.
+#[derive(Debug, Clone, Default)]
+pub struct CubeSet {
+    counts: Vec<(String, u32)>,
+}
+
+impl CubeSet {
+    pub fn new() -> CubeSet {
+        CubeSet { counts: Vec::new() }
+    }
+
+    /// Number of cubes of `color`, 0 if the color isn't in the set
+    pub fn get(&self, color: &str) -> u32 {
+        self.counts.iter()
+            .find(|(c, _)| c == color)
+            .map_or(0, |(_, count)| *count)
+    }
+
+    /// Sets the count of `color` and returns its previous count, if it had one
+    pub fn insert(&mut self, color: &str, count: u32) -> Option<u32> {
+        match self.counts.iter_mut().find(|(c, _)| c == color) {
+            Some((_, old)) => Some(std::mem::replace(old, count)),
+            None => {
+                self.counts.push((color.to_string(), count));
+                None
+            },
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.counts.iter().map(|(color, count)| (color.as_str(), *count))
+    }
+
+    pub fn colors(&self) -> impl Iterator<Item = &str> {
+        self.counts.iter().map(|(color, _)| color.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Raises every count to at least the count in `other`, adding the colors it doesn't have yet
+    pub fn include(&mut self, other: &CubeSet) {
+        for (color, count) in other.iter() {
+            if count > self.get(color) || !self.colors().any(|c| c == color) {
+                self.insert(color, count);
+            }
+        }
+    }
+
+    /// Whether every color in the set has at most as many cubes as in `bag`
+    pub fn fits_in(&self, bag: &CubeSet) -> bool {
+        self.iter().all(|(color, count)| count <= bag.get(color))
+    }
+
+    /// Product of the counts of every color in the set, 0 for an empty set
+    pub fn power(&self) -> u64 {
+        if self.is_empty() {
+            return 0;
+        }
+        self.iter().map(|(_, count)| count as u64).product()
+    }
+}
+
+/// Two sets are equal when they have the same colors with the same counts, in any order
+impl PartialEq for CubeSet {
+    fn eq(&self, other: &CubeSet) -> bool {
+        self.counts.len() == other.counts.len()
+            && self.iter().all(|(color, count)| other.colors().any(|c| c == color) && other.get(color) == count)
+    }
+}
+
+impl Eq for CubeSet {}
+
+impl<'a> FromIterator<(&'a str, u32)> for CubeSet {
+    fn from_iter<I: IntoIterator<Item = (&'a str, u32)>>(iter: I) -> CubeSet {
+        let mut set = CubeSet::new();
+        for (color, count) in iter {
+            set.insert(color, count);
+        }
+        set
+    }
+}
+
+impl fmt::Display for CubeSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (color, count)) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{} {}", count, color)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub id: u32,
+    pub rounds: Vec<CubeSet>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseGameError {
+    MissingHeader,
+    InvalidId(String),
+    InvalidCube(String),
+    DuplicateColor(String),
+}
+
+impl fmt::Display for ParseGameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseGameError::MissingHeader => write!(f, "expected the line to start with `Game <id>:`"),
+            ParseGameError::InvalidId(id) => write!(f, "`{}` is not a game id", id),
+            ParseGameError::InvalidCube(cube) => write!(f, "expected `<count> <color>`, found `{}`", cube),
+            ParseGameError::DuplicateColor(color) => write!(f, "{} appears twice in one round", color),
+        }
+    }
+}
+
+/// A parse error and the 1-based line it happened on
+#[derive(Debug, PartialEq)]
+pub struct LineError {
+    pub line: usize,
+    pub error: ParseGameError,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.error)
+    }
+}
+
+impl Game {
+    pub fn parse_bytes(line: &[u8]) -> Result<Game, ParseGameError> {
+        let lossy = |bytes: &[u8]| String::from_utf8_lossy(bytes).to_string();
+
+        let rest = line.strip_prefix(b"Game ").ok_or(ParseGameError::MissingHeader)?;
+        let (id, rounds) = input::split_once(rest, b':').ok_or(ParseGameError::MissingHeader)?;
+        let id = input::parse_u64(id)
+            .and_then(|id| u32::try_from(id).ok())
+            .ok_or_else(|| ParseGameError::InvalidId(lossy(id)))?;
+
+        let mut game = Game { id, rounds: Vec::new() };
+        if rounds.trim_ascii().is_empty() {
+            return Ok(game);
+        }
+
+        for round in rounds.split(|&b| b == b';') {
+            let mut set = CubeSet::new();
+            for cube in round.split(|&b| b == b',') {
+                let cube = cube.trim_ascii();
+                let invalid = || ParseGameError::InvalidCube(lossy(cube));
+
+                let (count, color) = input::split_once(cube, b' ').ok_or_else(invalid)?;
+                let count = input::parse_u64(count)
+                    .and_then(|count| u32::try_from(count).ok())
+                    .ok_or_else(invalid)?;
+                let color = str::from_utf8(color.trim_ascii()).map_err(|_| invalid())?;
+                if color.is_empty() || color.contains(char::is_whitespace) {
+                    return Err(invalid());
+                }
+
+                if set.insert(color, count).is_some() {
+                    return Err(ParseGameError::DuplicateColor(color.to_string()));
+                }
+            }
+            game.rounds.push(set);
+        }
+
+        Ok(game)
+    }
+
+    /// The fewest cubes of each color the bag must have held for this game
+    pub fn min_bag(&self) -> CubeSet {
+        let mut bag = CubeSet::new();
+        for round in &self.rounds {
+            bag.include(round);
+        }
+        bag
+    }
+
+    /// The power of the smallest bag, over the colors this game shows
+    pub fn power(&self) -> u64 {
+        self.min_bag().power()
+    }
+
+    /// Whether every round could have been drawn from `bag`
+    pub fn is_possible(&self, bag: &CubeSet) -> bool {
+        self.rounds.iter().all(|round| round.fits_in(bag))
+    }
+}
+
+impl FromStr for Game {
+    type Err = ParseGameError;
+
+    fn from_str(line: &str) -> Result<Game, ParseGameError> {
+        Game::parse_bytes(line.as_bytes())
+    }
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Game {}:", self.id)?;
+        for (i, round) in self.rounds.iter().enumerate() {
+            write!(f, "{} {}", if i > 0 { ";" } else { "" }, round)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses every line of the input into a game
+pub fn parse_games(contents: &[u8]) -> Result<Vec<Game>, LineError> {
+    input::lines(contents)
+        .enumerate()
+        .map(|(i, line)| Game::parse_bytes(line).map_err(|error| LineError { line: i + 1, error }))
+        .collect()
+}
+
+/// The bag from the puzzle: 12 red, 13 green and 14 blue cubes
+pub fn standard_bag() -> CubeSet {
+    CubeSet::from_iter([("red", 12), ("green", 13), ("blue", 14)])
+}
+
+/// Parses a bag written as `color=count` entries separated by commas or line breaks, e.g. `red=12,green=13`.
+/// Blank entries and `#` comments are ignored.
+pub fn parse_bag(text: &str) -> Result<CubeSet, String> {
+    let mut bag = CubeSet::new();
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or("");
+        for entry in line.split(',').map(|entry| entry.trim()).filter(|entry| !entry.is_empty()) {
+            let (color, count) = entry.split_once('=')
+                .ok_or(format!("Expected `color=count` in the bag, found `{}`", entry))?;
+            let (color, count) = (color.trim(), count.trim());
+
+            if color.is_empty() || color.contains(char::is_whitespace) {
+                return Err(format!("`{}` is not a color", color));
+            }
+            let count = count.parse::<u32>()
+                .map_err(|_| format!("`{}` is not a number of {} cubes", count, color))?;
+
+            if bag.insert(color, count).is_some() {
+                return Err(format!("{} is in the bag twice", color));
+            }
+        }
+    }
+
+    if bag.is_empty() {
+        return Err(String::from("The bag is empty"));
+    }
+    Ok(bag)
+}
+
+/// Reads the bag given with `--bag <spec>` or `--bag-file <file>`, or returns `default` if there is none
+pub fn bag_from_args(args: &[String], default: CubeSet) -> Result<CubeSet, String> {
+    let get_option = |name: &str| args.iter()
+        .position(|arg| arg == name)
+        .map(|position| args.get(position + 1).ok_or(format!("{} needs a value", name)));
+
+    if let Some(spec) = get_option("--bag") {
+        return parse_bag(spec?);
+    }
+    if let Some(path) = get_option("--bag-file") {
+        let path = path?;
+        let text = fs::read_to_string(path).map_err(|e| format!("Could not read bag file {}: {}", path, e))?;
+        return parse_bag(&text);
+    }
+    Ok(default)
+}
+
+/// Every color that appears in any of the games, in the order they first appear
+pub fn colors(games: &[Game]) -> Vec<&str> {
+    let mut colors: Vec<&str> = Vec::new();
+    for round in games.iter().flat_map(|game| &game.rounds) {
+        for color in round.colors() {
+            if !colors.contains(&color) {
+                colors.push(color);
+            }
+        }
+    }
+    colors
+}
+
+/// Part 1: the sum of the ids of the games that are possible with `bag`, in a `u64` like the streamed sum
+pub fn possible_id_sum(games: &[Game], bag: &CubeSet) -> u64 {
+    games.iter()
+        .filter(|game| game.is_possible(bag))
+        .map(|game| u64::from(game.id))
+        .sum()
+}
+
+/// Part 2: the sum of the powers of each game's smallest bag, each over the colors that game shows
+pub fn power_sum(games: &[Game]) -> u64 {
+    games.iter().map(Game::power).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+";
+
+    #[test]
+    fn parses_a_game() {
+        let game: Game = "Game 12: 3 blue, 4 red; 2 green".parse().expect("Test - parse");
+        assert_eq!(game.id, 12);
+        assert_eq!(game.rounds, vec![
+            CubeSet::from_iter([("red", 4), ("blue", 3)]),
+            CubeSet::from_iter([("green", 2)]),
+        ]);
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for line in EXAMPLE.lines().chain(["Game 7:", "Game 8: 0 red"]) {
+            let game: Game = line.parse().expect("Test - parse");
+            assert_eq!(game.to_string(), line);
+            assert_eq!(game.to_string().parse::<Game>(), Ok(game));
+        }
+    }
+
+    #[test]
+    fn reports_errors() {
+        assert_eq!("Round 1: 3 red".parse::<Game>(), Err(ParseGameError::MissingHeader));
+        assert_eq!("Game 1 3 red".parse::<Game>(), Err(ParseGameError::MissingHeader));
+        assert_eq!("Game x: 3 red".parse::<Game>(), Err(ParseGameError::InvalidId(String::from("x"))));
+        assert_eq!("Game 1: 3red".parse::<Game>(), Err(ParseGameError::InvalidCube(String::from("3red"))));
+        assert_eq!("Game 1: red 3".parse::<Game>(), Err(ParseGameError::InvalidCube(String::from("red 3"))));
+        assert_eq!("Game 1: 3 red;".parse::<Game>(), Err(ParseGameError::InvalidCube(String::new())));
+        assert_eq!("Game 1: 3 red, 2 red".parse::<Game>(), Err(ParseGameError::DuplicateColor(String::from("red"))));
+
+        let error = parse_games(b"Game 1: 3 red\nGame 2: 3 light red").expect_err("Test - error");
+        assert_eq!(error.to_string(), "Line 2: expected `<count> <color>`, found `3 light red`");
+    }
+
+    #[test]
+    fn min_bag_and_possible() {
+        let game: Game = EXAMPLE.lines().nth(2).expect("Test - line").parse().expect("Test - parse");
+        assert_eq!(game.min_bag(), CubeSet::from_iter([("green", 13), ("blue", 6), ("red", 20)]));
+        assert!(!game.is_possible(&standard_bag()));
+        assert_eq!(game.power(), 1560);
+        assert_eq!(CubeSet::from_iter([("red", 2), ("mauve", 3)]).power(), 6);
+        assert_eq!(CubeSet::new().power(), 0);
+    }
+
+    #[test]
+    fn example_answers() {
+        let games = parse_games(EXAMPLE.as_bytes()).expect("Test - parse");
+        assert_eq!(possible_id_sum(&games, &standard_bag()), 8);
+        assert_eq!(power_sum(&games), 2286);
+    }
+
+    #[test]
+    fn id_sum_fits_past_a_u32() {
+        let games = parse_games(b"Game 4294967295: 1 red\nGame 4294967294: 2 blue\n").expect("Test - parse");
+        assert_eq!(possible_id_sum(&games, &standard_bag()), 2 * u64::from(u32::MAX) - 1);
+    }
+
+    #[test]
+    fn parses_bags() {
+        let bag = parse_bag("red=12, green=13,blue = 14,yellow=5").expect("Test - parse");
+        assert_eq!(bag, CubeSet::from_iter([("red", 12), ("green", 13), ("blue", 14), ("yellow", 5)]));
+        assert_eq!(parse_bag("# bag\nred = 12\ngreen = 13 # lots\n\nblue = 14\n"), Ok(standard_bag()));
+
+        assert_eq!(parse_bag("red:12"), Err(String::from("Expected `color=count` in the bag, found `red:12`")));
+        assert_eq!(parse_bag("red=many"), Err(String::from("`many` is not a number of red cubes")));
+        assert_eq!(parse_bag("red=1,red=2"), Err(String::from("red is in the bag twice")));
+        assert_eq!(parse_bag(" = 2"), Err(String::from("`` is not a color")));
+        assert_eq!(parse_bag(""), Err(String::from("The bag is empty")));
+    }
+
+    #[test]
+    fn bag_from_args_uses_default() {
+        let args: Vec<String> = ["day2a", "input.txt", "--bag", "red=1"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(bag_from_args(&args, standard_bag()), Ok(CubeSet::from_iter([("red", 1)])));
+        assert_eq!(bag_from_args(&args[..2], standard_bag()), Ok(standard_bag()));
+        assert!(bag_from_args(&args[..3], standard_bag()).is_err());
+    }
+
+    #[test]
+    fn works_with_any_colors() {
+        let games = parse_games(b"Game 1: 2 yellow, 1 red; 3 mauve\nGame 2: 1 red, 1 yellow, 2 mauve\nGame 3: 6 yellow").expect("Test - parse");
+        assert_eq!(colors(&games), vec!["yellow", "red", "mauve"]);
+
+        let bag = parse_bag("red=1,yellow=5,mauve=3").expect("Test - parse");
+        assert_eq!(possible_id_sum(&games, &bag), 1 + 2);
+        // Colors the bag doesn't have can't be drawn at all
+        assert_eq!(possible_id_sum(&games, &standard_bag()), 0);
+
+        // Each game's power is over the colors it shows, whatever the bag
+        assert_eq!(power_sum(&games), 2 * 3 + 2 + 6);
+    }
+}
@@ -0,0 +1,248 @@
+//! How likely a game is for a given bag, shown by `day2a likelihood` (or `aoc day2 likelihood`).
+//!
+//! Each round draws the shown number of cubes from the bag without replacement and puts them back afterwards, so the
+//! chance of a round is the multivariate hypergeometric probability
+//!
+//! ```text
+//! C(n_red, x_red) * C(n_green, x_green) * ... / C(n, x)
+//! ```
+//!
+//! where `n_<color>` is the bag's count of a color, `x_<color>` the round's, and `n` and `x` their totals. A game's
+//! likelihood is the product over its rounds. It is kept as a sum of logarithms so long games don't underflow, and as
+//! an exact fraction while that fits in a `u128`.
+
+use std::fmt;
+
+use crate::{CubeSet, Game};
+
+/// A reduced fraction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ratio {
+    pub numerator: u128,
+    pub denominator: u128,
+}
+
+impl Ratio {
+    pub fn new(numerator: u128, denominator: u128) -> Ratio {
+        let divisor = gcd(numerator, denominator).max(1);
+        Ratio { numerator: numerator / divisor, denominator: denominator / divisor }
+    }
+
+    /// The product of two fractions, or `None` if it doesn't fit in a `u128`
+    pub fn checked_mul(self, other: Ratio) -> Option<Ratio> {
+        // Cross-reducing first keeps the intermediate products as small as they can be
+        let a = gcd(self.numerator, other.denominator).max(1);
+        let b = gcd(other.numerator, self.denominator).max(1);
+        Some(Ratio {
+            numerator: (self.numerator / a).checked_mul(other.numerator / b)?,
+            denominator: (self.denominator / b).checked_mul(other.denominator / a)?,
+        })
+    }
+}
+
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Number of ways to pick `k` of `n` cubes, or `None` if it doesn't fit in a `u128`
+pub fn binomial(n: u64, k: u64) -> Option<u128> {
+    if k > n {
+        return Some(0);
+    }
+
+    let k = k.min(n - k);
+    let mut result: u128 = 1;
+    for i in 1..=k as u128 {
+        // result * (n - k + i) is always divisible by i, so dividing out their common factor first stays exact
+        let divisor = gcd(result, i);
+        result = (result / divisor).checked_mul((n as u128 - k as u128 + i) / (i / divisor))?;
+    }
+    Some(result)
+}
+
+/// Natural logarithm of `binomial(n, k)`, for `k <= n`
+pub fn ln_binomial(n: u64, k: u64) -> f64 {
+    let k = k.min(n - k);
+    (1..=k).map(|i| ((n - k + i) as f64).ln() - (i as f64).ln()).sum()
+}
+
+/// Natural logarithm of the chance of drawing `round` from `bag`, negative infinity if it can't be drawn at all
+pub fn round_log_likelihood(round: &CubeSet, bag: &CubeSet) -> f64 {
+    if !round.fits_in(bag) {
+        return f64::NEG_INFINITY;
+    }
+
+    let bag_total: u64 = bag.iter().map(|(_, count)| count as u64).sum();
+    let drawn: u64 = round.iter().map(|(_, count)| count as u64).sum();
+    let ways: f64 = round.iter()
+        .map(|(color, count)| ln_binomial(bag.get(color) as u64, count as u64))
+        .sum();
+    ways - ln_binomial(bag_total, drawn)
+}
+
+/// Natural logarithm of the chance of every round of `game`, negative infinity if it isn't possible with `bag`
+pub fn log_likelihood(game: &Game, bag: &CubeSet) -> f64 {
+    game.rounds.iter().map(|round| round_log_likelihood(round, bag)).sum()
+}
+
+/// The exact chance of every round of `game`, or `None` if a numerator or denominator doesn't fit in a `u128`
+pub fn likelihood(game: &Game, bag: &CubeSet) -> Option<Ratio> {
+    let bag_total: u64 = bag.iter().map(|(_, count)| count as u64).sum();
+
+    let mut likelihood = Ratio::new(1, 1);
+    for round in &game.rounds {
+        if !round.fits_in(bag) {
+            return Some(Ratio::new(0, 1));
+        }
+
+        let drawn: u64 = round.iter().map(|(_, count)| count as u64).sum();
+        let mut ways: u128 = 1;
+        for (color, count) in round.iter() {
+            ways = ways.checked_mul(binomial(bag.get(color) as u64, count as u64)?)?;
+        }
+        likelihood = likelihood.checked_mul(Ratio::new(ways, binomial(bag_total, drawn)?))?;
+    }
+    Some(likelihood)
+}
+
+/// The bag that makes `game` most likely, searching every bag that holds its smallest bag plus at most `extra` more
+/// cubes, with the colors the game shows. Returns the bag and its log-likelihood.
+///
+/// More cubes don't always help: drawing 2 red and then 1 red and 1 blue is most likely with 3 red and 1 blue.
+/// Bags are tried from the fewest cubes up, so of two equally likely bags the smaller one is kept.
+pub fn most_likely_bag(game: &Game, extra: u32) -> (CubeSet, f64) {
+    let min_bag = game.min_bag();
+    let colors: Vec<&str> = min_bag.colors().collect();
+    let minimums: Vec<u32> = min_bag.iter().map(|(_, count)| count).collect();
+
+    let mut best = (min_bag.clone(), log_likelihood(game, &min_bag));
+    let mut extras = vec![0; colors.len()];
+    for total in 1..=extra {
+        for_each_split(total, &mut extras, 0, &mut |extras| {
+            let bag: CubeSet = colors.iter().copied()
+                .zip(minimums.iter().zip(extras).map(|(minimum, extra)| minimum + extra))
+                .collect();
+            let log_likelihood = log_likelihood(game, &bag);
+
+            // Rounding can make equally likely bags differ in the last bits
+            if log_likelihood > best.1 + 1e-9 {
+                best = (bag, log_likelihood);
+            }
+        });
+    }
+    best
+}
+
+/// Calls `f` with every way of splitting `total` over `extras[index..]`
+fn for_each_split(total: u32, extras: &mut [u32], index: usize, f: &mut impl FnMut(&[u32])) {
+    if index + 1 >= extras.len() {
+        if let Some(last) = extras.last_mut() {
+            *last = total;
+            f(extras);
+        }
+        return;
+    }
+
+    for count in 0..=total {
+        extras[index] = count;
+        for_each_split(total - count, extras, index + 1, f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> Game {
+        line.parse().expect("Test - parse")
+    }
+
+    #[test]
+    fn binomial_works() {
+        assert_eq!(binomial(5, 2), Some(10));
+        assert_eq!(binomial(5, 0), Some(1));
+        assert_eq!(binomial(2, 5), Some(0));
+        assert_eq!(binomial(100, 50), Some(100891344545564193334812497256));
+        assert_eq!(binomial(200, 100), None);
+        assert!((ln_binomial(100, 50) - (100891344545564193334812497256_f64).ln()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn likelihood_of_one_round() {
+        // 2 of the 3 red and 1 of the 2 blue out of 5 cubes: 3 * 2 / 10
+        let bag = CubeSet::from_iter([("red", 3), ("blue", 2)]);
+        let game = parse("Game 1: 2 red, 1 blue");
+        assert_eq!(likelihood(&game, &bag), Some(Ratio::new(3, 5)));
+        assert!((log_likelihood(&game, &bag) - 0.6_f64.ln()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn likelihood_multiplies_rounds() {
+        let bag = CubeSet::from_iter([("red", 3), ("blue", 2)]);
+        let game = parse("Game 1: 2 red, 1 blue; 1 blue; 5 red");
+        assert_eq!(likelihood(&game, &bag), Some(Ratio::new(0, 1)));
+        assert_eq!(log_likelihood(&game, &bag), f64::NEG_INFINITY);
+
+        // 3/5 for the first round and 2/5 for the second
+        let game = parse("Game 1: 2 red, 1 blue; 1 blue");
+        assert_eq!(likelihood(&game, &bag), Some(Ratio::new(6, 25)));
+        assert!((log_likelihood(&game, &bag) - 0.24_f64.ln()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn colors_missing_from_the_round_are_not_drawn() {
+        let bag = crate::standard_bag();
+        let game = parse("Game 1: 1 red");
+        assert_eq!(likelihood(&game, &bag), Some(Ratio::new(12, 39)));
+
+        let game = parse("Game 1: 1 yellow");
+        assert_eq!(likelihood(&game, &bag), Some(Ratio::new(0, 1)));
+    }
+
+    #[test]
+    fn long_games_do_not_underflow() {
+        let rounds = vec!["7 red, 7 green, 7 blue"; 400].join("; ");
+        let game = parse(&format!("Game 1: {}", rounds));
+        let bag = crate::standard_bag();
+
+        assert_eq!(likelihood(&game, &bag), None);
+        let log_likelihood = log_likelihood(&game, &bag);
+        assert!(log_likelihood.is_finite());
+        assert!((log_likelihood - 400.0 * round_log_likelihood(&game.rounds[0], &bag)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn most_likely_bag_of_one_round_is_the_round() {
+        let game = parse("Game 1: 3 blue, 4 red");
+        let (bag, log_likelihood) = most_likely_bag(&game, 10);
+        assert_eq!(bag, CubeSet::from_iter([("blue", 3), ("red", 4)]));
+        assert_eq!(log_likelihood, 0.0);
+    }
+
+    #[test]
+    fn most_likely_bag_can_be_bigger_than_the_smallest() {
+        // 3 red and 1 blue: 3/6 * 3/6 = 1/4, better than the smallest bag's 1/3 * 2/3 = 2/9 or 4 red's 6/10 * 4/10
+        let game = parse("Game 1: 2 red; 1 red, 1 blue");
+        let (bag, log_likelihood) = most_likely_bag(&game, 10);
+        assert_eq!(bag, CubeSet::from_iter([("red", 3), ("blue", 1)]));
+        assert!((log_likelihood - 0.25_f64.ln()).abs() < 1e-12);
+        assert_eq!(most_likely_bag(&game, 0).0, game.min_bag());
+    }
+
+    #[test]
+    fn most_likely_bag_prefers_fewer_cubes() {
+        // 1 red and 1 blue is exactly as likely as 2 of each
+        let game = parse("Game 1: 1 red; 1 blue");
+        assert_eq!(most_likely_bag(&game, 6).0, CubeSet::from_iter([("red", 1), ("blue", 1)]));
+        assert_eq!(most_likely_bag(&parse("Game 1:"), 6), (CubeSet::new(), 0.0));
+    }
+}
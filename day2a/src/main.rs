@@ -1,16 +1,148 @@
-use std::env;
-use std::fs;
+use std::{env, fs};
+
+use aoc::{breakdown::{self, Breakdown, Value}, input::{self, StreamArgs}};
+use day2::{likelihood, stats, CubeSet, Game, LineError};
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    let file_path = &args[1];
+    match args.get(1).map(|arg| arg.as_str()) {
+        Some("query") => return run_query(&args[2..]),
+        Some("likelihood") => return run_likelihood(&args[2..]),
+        _ => {}
+    }
+
+    // The file is the first argument unless it's an option, as in `day2a --stream`
+    let file_path = args.get(1)
+        .filter(|arg| !arg.starts_with("--"))
+        .map_or("input.txt", |arg| arg.as_str());
+    let bag = aoc::unwrap_or_exit(day2::bag_from_args(&args, day2::standard_bag()));
+
+    aoc::unwrap_or_exit(breakdown::check_not_streaming(&args));
+    if let Some(stream) = aoc::unwrap_or_exit(StreamArgs::from_args(&args, file_path)) {
+        let mut line_number = 0;
+        let sum = stream.sum_lines(|line| {
+            line_number += 1;
+            let game = aoc::unwrap_or_exit(Game::parse_bytes(line).map_err(|error| LineError { line: line_number, error }));
+            if game.is_possible(&bag) { u64::from(game.id) } else { 0 }
+        })
+            .expect("Should have been able to read the input");
+        print!("Sum of valid games: {}", sum);
+        return;
+    }
 
     println!("Reading from file: {}", file_path);
 
     let contents = fs::read_to_string(file_path)
         .expect("Should have been able to read the file");
 
-    let mut sum = 0;
+    if args.iter().any(|arg| arg == "--compare-parsers") {
+        input::compare_parsers(&contents, 100, parse_games_by_chars,
+            |contents| day2::parse_games(contents).expect("Should be able to parse the games"));
+        return;
+    }
+
+    let games = aoc::unwrap_or_exit(day2::parse_games(contents.as_bytes()));
+
+    if let Some(path) = breakdown::path_from_args(&args) {
+        get_breakdown(contents.as_bytes(), &games, &bag).write(path)
+            .expect("Should have been able to write the breakdown");
+        println!("Breakdown written to {}", path);
+    }
+
+    for (line, game) in input::lines(contents.as_bytes()).zip(&games) {
+        if !game.is_possible(&bag) {
+            let rounds = input::split_once(line, b':').map_or(&[][..], |(_, rounds)| rounds);
+            println!("Game {} is invalid. Game Info: {}", game.id, String::from_utf8_lossy(rounds));
+        }
+    }
+
+    let sum = day2::possible_id_sum(&games, &bag);
+
+    print!("Sum of valid games: {}", sum);
+}
+
+/// `day2a query [<file>] [--bag <spec> | --bag-file <file>] [--percent <k>]` prints statistics over the games
+/// instead of the answer. The file defaults to `input.txt` and the percentage to 100.
+fn run_query(args: &[String]) {
+    let games = read_games(args);
+    let bag = aoc::unwrap_or_exit(day2::bag_from_args(args, day2::standard_bag()));
+    let percent = match args.iter().position(|arg| arg == "--percent") {
+        Some(position) => aoc::unwrap_or_exit(args.get(position + 1)
+            .and_then(|percent| percent.parse::<f64>().ok())
+            .filter(|percent| (0.0..=100.0).contains(percent))
+            .ok_or("--percent needs a number from 0 to 100")),
+        None => 100.0,
+    };
+
+    print!("{}", stats::render(&games, &bag, percent));
+}
+
+/// `day2a likelihood [<file>] [--bag <spec> | --bag-file <file>] [--extra <n>]` prints how likely each game is with
+/// the bag, and the most likely bag with at most `n` (default 20) cubes more than the game's smallest bag
+fn run_likelihood(args: &[String]) {
+    let games = read_games(args);
+    let bag = aoc::unwrap_or_exit(day2::bag_from_args(args, day2::standard_bag()));
+    let extra = match args.iter().position(|arg| arg == "--extra") {
+        Some(position) => aoc::unwrap_or_exit(args.get(position + 1)
+            .and_then(|extra| extra.parse::<u32>().ok())
+            .ok_or("--extra needs a number of cubes")),
+        None => 20,
+    };
+
+    println!("Likelihood with {}, most likely bag with up to {} more cubes than needed:", bag, extra);
+    for game in &games {
+        let exact = match likelihood::likelihood(game, &bag) {
+            Some(ratio) => format!(" = {}", ratio),
+            None => String::new(),
+        };
+        let (best, best_log_likelihood) = likelihood::most_likely_bag(game, extra);
+        println!("Game {}: ln L = {:.4}{}; most likely {} (ln L = {:.4})",
+            game.id, likelihood::log_likelihood(game, &bag), exact, best, best_log_likelihood);
+    }
+}
+
+/// Parses the games of a subcommand's input, the first argument unless it's an option
+fn read_games(args: &[String]) -> Vec<Game> {
+    let file_path = args.first()
+        .filter(|arg| !arg.starts_with("--"))
+        .map_or("input.txt", |arg| arg.as_str());
+
+    let contents = fs::read(file_path)
+        .expect("Should have been able to read the file");
+    aoc::unwrap_or_exit(day2::parse_games(&contents))
+}
+
+/// A row per game with the most cubes of each color it showed, for every color in the bag or the input
+fn get_breakdown(contents: &[u8], games: &[Game], bag: &CubeSet) -> Breakdown {
+    let mut colors: Vec<&str> = bag.colors().collect();
+    for color in day2::colors(games) {
+        if !colors.contains(&color) {
+            colors.push(color);
+        }
+    }
+
+    let mut fields = vec![String::from("game")];
+    fields.extend(colors.iter().map(|color| format!("max_{}", color)));
+    fields.push(String::from("valid"));
+    let fields: Vec<&str> = fields.iter().map(|field| field.as_str()).collect();
+
+    let mut breakdown = Breakdown::new(&fields);
+    for (i, (line, game)) in input::lines(contents).zip(games).enumerate() {
+        let min_bag = game.min_bag();
+        let valid = game.is_possible(bag);
+
+        let mut values = vec![Value::number(game.id)];
+        values.extend(colors.iter().map(|color| Value::number(min_bag.get(color))));
+        values.push(Value::Bool(valid));
+
+        breakdown.add(i + 1, line, if valid { game.id } else { 0 }, values);
+    }
+    breakdown
+}
+
+/// The original parser, kept as a reference for the byte parser in the `day2` library
+fn parse_games_by_chars(contents: &str) -> Vec<Game> {
+    let mut games = Vec::new();
     for game in contents.lines() {
         let mut subgames = game.split(':');
         let game_info_str = subgames.next()
@@ -26,10 +158,10 @@ fn main() {
 
         let rounds = subgames.next()
             .expect("Second Element Should be Rounds");
-            
 
-        let mut game_is_valid = true;
-        'outer: for round in rounds.split(';') {
+        let mut game = Game { id: game_num, rounds: Vec::new() };
+        for round in rounds.split(';') {
+            let mut cubes = CubeSet::new();
             for cube in round.split(',') {
                 let mut iter = cube.split(' ');
                 iter.next(); // Skip first element which is empty string lol
@@ -40,37 +172,50 @@ fn main() {
                 let color = iter.next()
                     .expect("Should be able to get second element - color of the cube");
 
-                match color {
-                    "red" => {
-                        if number > 12 {
-                            game_is_valid = false;
-                            break 'outer;
-                        }
-                    },
-                    "green" => {
-                        if number > 13 {
-                            game_is_valid = false;
-                            break 'outer;
-                        }
-                    },
-                    "blue" => {
-                        if number > 14 {
-                            game_is_valid = false;
-                            break 'outer;
-                        }
-                    },
-                    x => panic!("Invalid color given: {}", x),
-                }
+                cubes.insert(color, number);
             }
+            game.rounds.push(cubes);
         }
 
-        if game_is_valid {
-            sum += game_num;
-        }
-        else {
-            println!("Game {} is invalid. Game Info: {}", game_num, rounds);
-        }
+        games.push(game);
     }
+    games
+}
 
-    print!("Sum of valid games: {}", sum);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_games_matches_parse_games_by_chars() {
+        let contents = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\nGame 12: 20 red\n";
+        let games = day2::parse_games(contents.as_bytes()).expect("Test - parse");
+
+        assert_eq!(games, parse_games_by_chars(contents));
+        assert_eq!(games[1].to_string(), "Game 12: 20 red");
+    }
+
+    #[test]
+    fn breakdown_has_a_column_per_color() {
+        let contents = b"Game 1: 3 yellow, 4 red\n";
+        let games = day2::parse_games(contents).expect("Test - parse");
+        let bag = day2::parse_bag("red=5,blue=1").expect("Test - bag");
+        let csv = get_breakdown(contents, &games, &bag).to_csv();
+        assert_eq!(csv, "\
+line,raw,contribution,game,max_red,max_blue,max_yellow,valid
+1,\"Game 1: 3 yellow, 4 red\",0,1,4,0,3,false
+");
+    }
+
+    #[test]
+    fn breakdown_marks_invalid_games() {
+        let contents = b"Game 1: 3 blue, 4 red; 2 red\nGame 2: 20 red\n";
+        let games = day2::parse_games(contents).expect("Test - parse");
+        let csv = get_breakdown(contents, &games, &day2::standard_bag()).to_csv();
+        assert_eq!(csv, "\
+line,raw,contribution,game,max_red,max_green,max_blue,valid
+1,\"Game 1: 3 blue, 4 red; 2 red\",1,1,4,0,3,true
+2,Game 2: 20 red,0,2,20,0,0,false
+");
+    }
 }
@@ -0,0 +1,226 @@
+//! Statistics over parsed games, shown by `day2a query` (or `aoc day2 query`).
+//!
+//! Everything here starts from each game's smallest bag, the most cubes of each color it showed in any round.
+
+use std::fmt::Write;
+
+use crate::{CubeSet, Game};
+
+pub struct ColorStats {
+    pub color: String,
+    /// Largest count of the color in any game's smallest bag
+    pub max: u32,
+    pub mean: f64,
+    /// How many games needed each count of the color, sorted by count
+    pub distribution: Vec<(u32, usize)>,
+}
+
+/// Max, mean and distribution of each color over the games' smallest bags. A game without a color needs 0 of it.
+pub fn color_stats(games: &[Game]) -> Vec<ColorStats> {
+    let min_bags: Vec<CubeSet> = games.iter().map(|game| game.min_bag()).collect();
+
+    crate::colors(games).into_iter()
+        .map(|color| {
+            let mut counts: Vec<u32> = min_bags.iter().map(|bag| bag.get(color)).collect();
+            counts.sort();
+
+            let mut distribution: Vec<(u32, usize)> = Vec::new();
+            for &count in &counts {
+                match distribution.last_mut() {
+                    Some((last, games)) if *last == count => *games += 1,
+                    _ => distribution.push((count, 1)),
+                }
+            }
+
+            ColorStats {
+                color: color.to_string(),
+                max: counts.last().copied().unwrap_or(0),
+                mean: counts.iter().map(|&count| count as f64).sum::<f64>() / counts.len().max(1) as f64,
+                distribution,
+            }
+        })
+        .collect()
+}
+
+/// For each color of the bag and the input, the ids of the games that need more of it than the bag has
+pub fn blocked_games<'a>(games: &'a [Game], bag: &'a CubeSet) -> Vec<(&'a str, Vec<u32>)> {
+    let mut colors: Vec<&str> = bag.colors().collect();
+    for color in crate::colors(games) {
+        if !colors.contains(&color) {
+            colors.push(color);
+        }
+    }
+
+    let min_bags: Vec<(u32, CubeSet)> = games.iter().map(|game| (game.id, game.min_bag())).collect();
+    colors.into_iter()
+        .map(|color| {
+            let blocked = min_bags.iter()
+                .filter(|(_, min_bag)| min_bag.get(color) > bag.get(color))
+                .map(|(id, _)| *id)
+                .collect();
+            (color, blocked)
+        })
+        .collect()
+}
+
+/// The bag with the fewest cubes in total that admits at least `percent` of the games, over the colors in the input.
+/// Returns the bag and how many games it admits.
+///
+/// Every count in the best bag is one of the games' counts, so the search tries those for all colors but the last
+/// and then takes just enough of the last color. That is exponential in the number of colors, which is fine for the
+/// handful a puzzle has.
+pub fn tightest_bag(games: &[Game], percent: f64) -> (CubeSet, usize) {
+    let colors = crate::colors(games);
+    let needs: Vec<Vec<u32>> = games.iter()
+        .map(|game| {
+            let min_bag = game.min_bag();
+            colors.iter().map(|color| min_bag.get(color)).collect()
+        })
+        .collect();
+
+    let required = ((games.len() as f64 * percent / 100.0).ceil() as usize).min(games.len());
+    let mut best = None;
+    if required > 0 && !colors.is_empty() {
+        let all_games: Vec<usize> = (0..games.len()).collect();
+        search_bag(&needs, required, &all_games, &mut Vec::new(), &mut best);
+    }
+
+    let counts = best.map_or(vec![0; colors.len()], |(_, counts)| counts);
+    let bag: CubeSet = colors.iter().copied().zip(counts).collect();
+    let admitted = games.iter().filter(|game| game.is_possible(&bag)).count();
+    (bag, admitted)
+}
+
+/// Picks the count of the next color for the games in `candidates`, keeping the smallest total in `best`
+fn search_bag(needs: &[Vec<u32>], required: usize, candidates: &[usize], chosen: &mut Vec<u32>, best: &mut Option<(u64, Vec<u32>)>) {
+    let color = chosen.len();
+    let chosen_total: u64 = chosen.iter().map(|&count| count as u64).sum();
+
+    let mut values: Vec<u32> = candidates.iter().map(|&game| needs[game][color]).collect();
+    values.sort();
+
+    if color == needs[0].len() - 1 {
+        // The last color only needs enough cubes for the required number of games
+        let count = values[required - 1];
+        let total = chosen_total + count as u64;
+        if best.as_ref().is_none_or(|(best_total, _)| total < *best_total) {
+            let mut counts = chosen.clone();
+            counts.push(count);
+            *best = Some((total, counts));
+        }
+        return;
+    }
+
+    values.dedup();
+    for count in values {
+        // Larger counts only make the total bigger
+        if best.as_ref().is_some_and(|(best_total, _)| chosen_total + count as u64 >= *best_total) {
+            break;
+        }
+
+        let fitting: Vec<usize> = candidates.iter().copied().filter(|&game| needs[game][color] <= count).collect();
+        if fitting.len() < required {
+            continue;
+        }
+
+        chosen.push(count);
+        search_bag(needs, required, &fitting, chosen, best);
+        chosen.pop();
+    }
+}
+
+/// Renders every statistic as plain text
+pub fn render(games: &[Game], bag: &CubeSet, percent: f64) -> String {
+    let mut text = String::new();
+
+    writeln!(text, "Smallest bag per game:").unwrap();
+    for game in games {
+        writeln!(text, "  Game {}: {}", game.id, game.min_bag()).unwrap();
+    }
+
+    writeln!(text, "\nGames blocked by each color with {}:", bag).unwrap();
+    for (color, blocked) in blocked_games(games, bag) {
+        let ids: Vec<String> = blocked.iter().map(|id| id.to_string()).collect();
+        writeln!(text, "  {}: {}", color, if ids.is_empty() { String::from("none") } else { ids.join(", ") }).unwrap();
+    }
+
+    writeln!(text, "\nPer color:").unwrap();
+    writeln!(text, "  {:<10} {:>5} {:>8}   distribution (count:games)", "color", "max", "mean").unwrap();
+    for stats in color_stats(games) {
+        let distribution: Vec<String> = stats.distribution.iter().map(|(count, games)| format!("{}:{}", count, games)).collect();
+        writeln!(text, "  {:<10} {:>5} {:>8.2}   {}", stats.color, stats.max, stats.mean, distribution.join(" ")).unwrap();
+    }
+
+    let (tightest, admitted) = tightest_bag(games, percent);
+    writeln!(text, "\nTightest bag admitting at least {}% of games: {} ({} of {} games)",
+        percent, tightest, admitted, games.len()).unwrap();
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &[u8] = b"\
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+";
+
+    fn games() -> Vec<Game> {
+        crate::parse_games(EXAMPLE).expect("Test - parse")
+    }
+
+    #[test]
+    fn color_stats_work() {
+        let stats = color_stats(&games());
+        let blue = stats.iter().find(|stats| stats.color == "blue").expect("Test - blue");
+        assert_eq!(blue.max, 15);
+        assert_eq!(blue.mean, (6 + 4 + 6 + 15 + 2) as f64 / 5.0);
+        assert_eq!(blue.distribution, vec![(2, 1), (4, 1), (6, 2), (15, 1)]);
+    }
+
+    #[test]
+    fn blocked_games_work() {
+        let (games, bag) = (games(), crate::standard_bag());
+        assert_eq!(blocked_games(&games, &bag), vec![("red", vec![3, 4]), ("green", vec![]), ("blue", vec![4])]);
+    }
+
+    #[test]
+    fn tightest_bag_admits_everything_at_100_percent() {
+        let (bag, admitted) = tightest_bag(&games(), 100.0);
+        assert_eq!(bag, CubeSet::from_iter([("red", 20), ("green", 13), ("blue", 15)]));
+        assert_eq!(admitted, 5);
+    }
+
+    #[test]
+    fn tightest_bag_drops_expensive_games() {
+        // Leaving out game 3 (20 red, 13 green) saves more than leaving out game 4 (14 red, 15 blue)
+        let (bag, admitted) = tightest_bag(&games(), 80.0);
+        assert_eq!(bag, CubeSet::from_iter([("red", 14), ("green", 3), ("blue", 15)]));
+        assert_eq!(admitted, 4);
+
+        let (bag, admitted) = tightest_bag(&games(), 20.0);
+        assert_eq!(bag, CubeSet::from_iter([("red", 1), ("green", 3), ("blue", 4)]));
+        assert_eq!(admitted, 1);
+    }
+
+    #[test]
+    fn tightest_bag_of_nothing() {
+        let (bag, admitted) = tightest_bag(&games(), 0.0);
+        assert_eq!(bag, CubeSet::from_iter([("red", 0), ("green", 0), ("blue", 0)]));
+        assert_eq!(admitted, 0);
+        assert_eq!(tightest_bag(&[], 50.0), (CubeSet::new(), 0));
+    }
+
+    #[test]
+    fn render_has_every_section() {
+        let text = render(&games(), &crate::standard_bag(), 100.0);
+        assert!(text.contains("  Game 3: 13 green, 6 blue, 20 red\n"));
+        assert!(text.contains("  red: 3, 4\n  green: none\n"));
+        assert!(text.contains("Tightest bag admitting at least 100% of games: 15 blue, 20 red, 13 green (5 of 5 games)"));
+    }
+}
@@ -1,17 +1,63 @@
-use std::fs;
+use std::{env, fs, io::{self, BufRead}, process};
+
+use aoc::{breakdown::{self, Breakdown, Value}, input::{self, StreamArgs}};
+use day2::{Game, LineError};
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    // Each game's power only depends on the cubes it shows
+    if args.iter().any(|arg| arg == "--bag" || arg == "--bag-file") {
+        eprintln!("--bag can't change day2b's answer, the power of a game is over the colors it shows");
+        process::exit(1);
+    }
+
+    aoc::unwrap_or_exit(breakdown::check_not_streaming(&args));
+    if let Some(stream) = aoc::unwrap_or_exit(StreamArgs::from_args(&args, "input.txt")) {
+        let sum = stream.open()
+            .and_then(|reader| stream_power_sum(reader, stream.report_every))
+            .expect("Should have been able to read the input");
+        print!("The sum of all games is {}", sum);
+        return;
+    }
+
     let contents = fs::read_to_string("input.txt")
         .expect("Unable to find file input.txt");
 
-    let mut sum = 0;
-    for line in contents.lines() {
-        sum += get_game_score(line);
+    if args.iter().any(|arg| arg == "--compare-parsers") {
+        input::compare_parsers(&contents, 100, sum_game_scores,
+            |contents| day2::power_sum(&day2::parse_games(contents).expect("Should be able to parse the games")));
+        return;
     }
 
+    let games = aoc::unwrap_or_exit(day2::parse_games(contents.as_bytes()));
+
+    if let Some(path) = breakdown::path_from_args(&args) {
+        get_breakdown(contents.as_bytes(), &games).write(path)
+            .expect("Should have been able to write the breakdown");
+        println!("Breakdown written to {}", path);
+    }
+
+    let sum = day2::power_sum(&games);
+
     print!("The sum of all games is {}", sum);
 }
 
+/// Same as `day2::power_sum`, one line at a time
+fn stream_power_sum<R: BufRead>(reader: R, report_every: Option<u64>) -> io::Result<u64> {
+    let mut line_number = 0;
+    input::sum_lines(reader, report_every, |line| {
+        line_number += 1;
+        let game = aoc::unwrap_or_exit(Game::parse_bytes(line).map_err(|error| LineError { line: line_number, error }));
+        game.power()
+    })
+}
+
+/// The original solution, kept as a reference for the `day2` library
+fn sum_game_scores(contents: &str) -> u64 {
+    contents.lines().map(|game| get_game_score(game) as u64).sum()
+}
+
 fn get_game_score(game: &str) -> i32 {
     let mut x = game.split(':');
     x.next(); // Skip first
@@ -53,4 +99,50 @@ fn get_game_score(game: &str) -> i32 {
     }
 
     max_blue_seen * max_green_seen * max_red_seen
-}
\ No newline at end of file
+}
+
+/// A row per game with the most cubes of each color it showed, for every color in the input
+fn get_breakdown(contents: &[u8], games: &[Game]) -> Breakdown {
+    let colors = day2::colors(games);
+
+    let fields: Vec<String> = colors.iter().map(|color| format!("max_{}", color)).collect();
+    let fields: Vec<&str> = fields.iter().map(|field| field.as_str()).collect();
+
+    let mut breakdown = Breakdown::new(&fields);
+    for (i, (line, game)) in input::lines(contents).zip(games).enumerate() {
+        let min_bag = game.min_bag();
+        let values = colors.iter().map(|color| Value::number(min_bag.get(color))).collect();
+        breakdown.add(i + 1, line, min_bag.power(), values);
+    }
+    breakdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn library_matches_get_game_score() {
+        let contents = "\
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+";
+        let games = day2::parse_games(contents.as_bytes()).expect("Test - parse");
+        assert_eq!(day2::power_sum(&games), sum_game_scores(contents));
+        assert_eq!(day2::power_sum(&games), 48 + 12 + 1560);
+    }
+
+    #[test]
+    fn streaming_matches_parsing_first() {
+        let contents = "\
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green, 5 yellow; 3 green, 4 blue, 1 red
+Game 3: 8 green, 6 blue, 20 red, 2 mauve
+";
+        let games = day2::parse_games(contents.as_bytes()).expect("Test - parse");
+        let streamed = stream_power_sum(contents.as_bytes(), None).expect("Test - stream");
+        assert_eq!(streamed, day2::power_sum(&games));
+        assert_eq!(streamed, 48 + 4 * 3 * 5 + 8 * 6 * 20 * 2);
+    }
+}
@@ -0,0 +1,345 @@
+//! Engine schematic model shared by both parts of day 3.
+//!
+//! [`Schematic::parse`] finds every number with its span and value and every symbol with its position, and links
+//! each number to the symbols around it (including diagonally) once, up front. Part 1 is the sum of the numbers next
+//! to any symbol and part 2 the sum of the products of the two numbers around each `*` that has exactly two; both are
+//! queries over those links. Other rules for which symbols count are in [`rules`], and [`render`] draws the
+//! schematic with what was counted.
+
+pub mod render;
+pub mod rules;
+pub mod stream;
+
+use std::fmt;
+
+use aoc::input;
+use rules::{SymbolRule, ValueTooLarge};
+
+/// A run of digits on one row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Number {
+    pub value: u64,
+    pub row: usize,
+    /// First column of the number
+    pub start: usize,
+    /// Column just past the number's last digit
+    pub end: usize,
+}
+
+/// Any character that is neither a digit nor `.`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub symbol: char,
+    pub row: usize,
+    pub column: usize,
+}
+
+/// What cells outside the schematic are read as, `.` unless `--padding <char>` is given
+pub const DEFAULT_PADDING: u8 = b'.';
+
+/// A number too long for a `u64`, with its 1-based position
+#[derive(Debug, PartialEq)]
+pub struct NumberTooLarge {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for NumberTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Line {}: the number at column {} doesn't fit in a u64", self.line, self.column)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Schematic {
+    pub rows: Vec<Vec<u8>>,
+    pub numbers: Vec<Number>,
+    pub symbols: Vec<Symbol>,
+    /// Indexes into `symbols` of the symbols around each number
+    number_symbols: Vec<Vec<usize>>,
+    /// Indexes into `numbers` of the numbers around each symbol
+    symbol_numbers: Vec<Vec<usize>>,
+    /// Whether each number has a cell outside the schematic around it and the padding is a symbol
+    number_padded: Vec<bool>,
+}
+
+impl Schematic {
+    pub fn parse(contents: &[u8]) -> Result<Schematic, NumberTooLarge> {
+        Schematic::parse_padded(contents, DEFAULT_PADDING)
+    }
+
+    /// Parses with every cell outside the schematic read as `padding`: above the first row, below the last, left of
+    /// the first column and past the end of each row, so rows can have different lengths. A symbol as padding makes
+    /// the numbers on the edges part numbers, but the padding isn't a [`Symbol`] itself and never makes a gear.
+    pub fn parse_padded(contents: &[u8], padding: u8) -> Result<Schematic, NumberTooLarge> {
+        Schematic::from_rows_padded(input::lines(contents).map(|line| line.to_vec()).collect(), padding)
+    }
+
+    pub fn from_rows(rows: Vec<Vec<u8>>) -> Result<Schematic, NumberTooLarge> {
+        Schematic::from_rows_padded(rows, DEFAULT_PADDING)
+    }
+
+    pub fn from_rows_padded(rows: Vec<Vec<u8>>, padding: u8) -> Result<Schematic, NumberTooLarge> {
+        let mut numbers = Vec::new();
+        let mut symbols = Vec::new();
+        // The index into `symbols` of the symbol in each cell, to look up a number's neighbors
+        let mut symbol_at: Vec<Vec<Option<usize>>> = Vec::new();
+
+        for (row, line) in rows.iter().enumerate() {
+            let (row_numbers, row_symbols) = parse_row(row, line)?;
+            numbers.extend(row_numbers);
+
+            symbol_at.push(vec![None; line.len()]);
+            for symbol in row_symbols {
+                symbol_at[row][symbol.column] = Some(symbols.len());
+                symbols.push(symbol);
+            }
+        }
+
+        let padding_is_symbol = is_symbol(padding);
+        let mut number_symbols = vec![Vec::new(); numbers.len()];
+        let mut symbol_numbers = vec![Vec::new(); symbols.len()];
+        let mut number_padded = vec![false; numbers.len()];
+        for (i, number) in numbers.iter().enumerate() {
+            // Cells left of the first column or above the first row are outside the schematic too
+            if padding_is_symbol && (number.start == 0 || number.row == 0) {
+                number_padded[i] = true;
+            }
+
+            for row in number.row.saturating_sub(1)..=number.row + 1 {
+                for column in number.start.saturating_sub(1)..=number.end {
+                    match symbol_at.get(row).and_then(|line| line.get(column)) {
+                        Some(&Some(symbol)) => {
+                            number_symbols[i].push(symbol);
+                            symbol_numbers[symbol].push(i);
+                        },
+                        Some(None) => {},
+                        None => number_padded[i] |= padding_is_symbol,
+                    }
+                }
+            }
+        }
+
+        Ok(Schematic { rows, numbers, symbols, number_symbols, symbol_numbers, number_padded })
+    }
+
+    /// The symbols around `self.numbers[number]`
+    pub fn symbols_next_to(&self, number: usize) -> impl Iterator<Item = &Symbol> {
+        self.number_symbols[number].iter().map(|&symbol| &self.symbols[symbol])
+    }
+
+    /// The numbers around `self.symbols[symbol]`, in the order they appear in the schematic
+    pub fn numbers_next_to(&self, symbol: usize) -> impl Iterator<Item = &Number> {
+        self.symbol_numbers[symbol].iter().map(|&number| &self.numbers[number])
+    }
+
+    /// Whether `self.numbers[number]` is next to a symbol, or to the edge when the padding is a symbol
+    pub fn is_part_number(&self, number: usize) -> bool {
+        !self.number_symbols[number].is_empty() || self.number_padded[number]
+    }
+
+    /// Whether `self.numbers[number]` is next to the edge of the schematic and the padding is a symbol
+    pub fn is_padded(&self, number: usize) -> bool {
+        self.number_padded[number]
+    }
+
+    /// The numbers next to at least one symbol
+    pub fn part_numbers(&self) -> impl Iterator<Item = &Number> {
+        self.numbers.iter()
+            .enumerate()
+            .filter(|&(i, _)| self.is_part_number(i))
+            .map(|(_, number)| number)
+    }
+
+    /// Every `*` next to exactly two numbers, with those numbers
+    pub fn gears(&self) -> impl Iterator<Item = (&Symbol, &Number, &Number)> {
+        self.symbols.iter()
+            .zip(&self.symbol_numbers)
+            .filter(|(symbol, numbers)| symbol.symbol == '*' && numbers.len() == 2)
+            .map(|(symbol, numbers)| (symbol, &self.numbers[numbers[0]], &self.numbers[numbers[1]]))
+    }
+
+    /// Part 1: the sum of the part numbers
+    pub fn part_sum(&self) -> Result<u64, ValueTooLarge> {
+        self.part_numbers().try_fold(0u64, |sum, number| {
+            sum.checked_add(number.value).ok_or_else(|| ValueTooLarge::part_sum(number))
+        })
+    }
+
+    /// Part 2: the sum of every gear's ratio, the product of its two numbers
+    pub fn gear_ratio_sum(&self) -> Result<u64, ValueTooLarge> {
+        self.rule_sum(&SymbolRule::gear())
+    }
+}
+
+/// The numbers and symbols on one row of the schematic
+pub(crate) fn parse_row(row: usize, line: &[u8]) -> Result<(Vec<Number>, Vec<Symbol>), NumberTooLarge> {
+    let mut numbers = Vec::new();
+    let mut symbols = Vec::new();
+
+    let mut column = 0;
+    while column < line.len() {
+        let b = line[column];
+        if b.is_ascii_digit() {
+            let start = column;
+            while column < line.len() && line[column].is_ascii_digit() {
+                column += 1;
+            }
+            let value = input::parse_u64(&line[start..column])
+                .ok_or(NumberTooLarge { line: row + 1, column: start + 1 })?;
+            numbers.push(Number { value, row, start, end: column });
+            continue;
+        }
+
+        if is_symbol(b) {
+            symbols.push(Symbol { symbol: b as char, row, column });
+        }
+        column += 1;
+    }
+
+    Ok((numbers, symbols))
+}
+
+/// Any character that is neither a digit nor `.`
+pub(crate) fn is_symbol(b: u8) -> bool {
+    !b.is_ascii_digit() && b != b'.'
+}
+
+/// Reads `--padding <char>`, a single ASCII character that isn't a digit
+pub fn padding_from_args(args: &[String]) -> Result<u8, String> {
+    let Some(position) = args.iter().position(|arg| arg == "--padding") else {
+        return Ok(DEFAULT_PADDING);
+    };
+
+    match args.get(position + 1).map(|padding| padding.as_bytes()) {
+        Some(&[padding]) if padding.is_ascii() && !padding.is_ascii_digit() => Ok(padding),
+        Some(_) => Err(String::from("--padding needs a single ASCII character that isn't a digit")),
+        None => Err(String::from("--padding needs a value")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &[u8] = b"\
+467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..
+";
+
+    #[test]
+    fn parse_finds_numbers_and_symbols() {
+        let schematic = Schematic::parse(EXAMPLE).expect("Test - parse");
+        assert_eq!(schematic.numbers.len(), 10);
+        assert_eq!(schematic.numbers[1], Number { value: 114, row: 0, start: 5, end: 8 });
+        assert_eq!(schematic.symbols.len(), 6);
+        assert_eq!(schematic.symbols[0], Symbol { symbol: '*', row: 1, column: 3 });
+    }
+
+    #[test]
+    fn adjacency_goes_both_ways() {
+        let schematic = Schematic::parse(EXAMPLE).expect("Test - parse");
+
+        let around_star: Vec<u64> = schematic.numbers_next_to(0).map(|number| number.value).collect();
+        assert_eq!(around_star, vec![467, 35]);
+
+        let around_617: Vec<char> = schematic.symbols_next_to(4).map(|symbol| symbol.symbol).collect();
+        assert_eq!(around_617, vec!['*']);
+        assert_eq!(schematic.symbols_next_to(1).count(), 0);
+    }
+
+    #[test]
+    fn example_answers() {
+        let schematic = Schematic::parse(EXAMPLE).expect("Test - parse");
+        assert_eq!(schematic.part_sum(), Ok(4361));
+        assert_eq!(schematic.gear_ratio_sum(), Ok(467835));
+    }
+
+    #[test]
+    fn numbers_touching_several_symbols_count_once() {
+        let schematic = Schematic::parse(b"#.#\n.5.\n").expect("Test - parse");
+        assert_eq!(schematic.symbols_next_to(0).count(), 2);
+        assert_eq!(schematic.part_sum(), Ok(5));
+    }
+
+    #[test]
+    fn the_same_number_twice_is_two_numbers() {
+        // The old part 2 de-duplicated numbers by their cells, so two 12s next to a gear are still a pair
+        let schematic = Schematic::parse(b"12*12\n").expect("Test - parse");
+        assert_eq!(schematic.gear_ratio_sum(), Ok(144));
+    }
+
+    #[test]
+    fn corners_and_edges() {
+        // A number in each corner, each with its symbol diagonally inwards, and one in the middle of each edge
+        let schematic = Schematic::parse(b"\
+1...5...2
+.#.....#.
+6.......7
+.#.....#.
+3...8...4
+").expect("Test - parse");
+        let parts: Vec<u64> = schematic.part_numbers().map(|number| number.value).collect();
+        assert_eq!(parts, vec![1, 2, 6, 7, 3, 4]);
+        assert!(!schematic.is_part_number(1));
+    }
+
+    #[test]
+    fn ragged_rows_are_safe() {
+        // The symbol on the long second row is past the end of the first and last rows
+        let schematic = Schematic::parse(b"12\n..*.\n\n3\n.....7\n99\n").expect("Test - parse");
+        let parts: Vec<u64> = schematic.part_numbers().map(|number| number.value).collect();
+        assert_eq!(parts, vec![12]);
+        assert_eq!(schematic.numbers.len(), 4);
+
+        let schematic = Schematic::parse(b"\n\n.1\n#\n").expect("Test - parse");
+        assert_eq!(schematic.part_sum(), Ok(1));
+    }
+
+    #[test]
+    fn symbol_padding_counts_numbers_on_the_edges() {
+        let contents = b"1...\n.5..\n..\n...7\n.22.\n";
+        assert_eq!(Schematic::parse(contents).expect("Test - parse").part_sum(), Ok(0));
+
+        // 1 is in the corner, 5 and 7 next to the end of the short row and 22 on the last row
+        let schematic = Schematic::parse_padded(contents, b'#').expect("Test - parse");
+        let parts: Vec<u64> = schematic.part_numbers().map(|number| number.value).collect();
+        assert_eq!(parts, vec![1, 5, 7, 22]);
+        assert!(schematic.is_padded(0));
+        assert!(schematic.symbols.is_empty());
+
+        // The padding is never a gear
+        let schematic = Schematic::parse_padded(b"2\n3\n", b'*').expect("Test - parse");
+        assert_eq!(schematic.part_sum(), Ok(5));
+        assert_eq!(schematic.gear_ratio_sum(), Ok(0));
+    }
+
+    #[test]
+    fn padding_from_args_works() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<String>>();
+        assert_eq!(padding_from_args(&args(&["day3a"])), Ok(b'.'));
+        assert_eq!(padding_from_args(&args(&["day3a", "--padding", "#"])), Ok(b'#'));
+        assert!(padding_from_args(&args(&["day3a", "--padding", "5"])).is_err());
+        assert!(padding_from_args(&args(&["day3a", "--padding", "##"])).is_err());
+        assert!(padding_from_args(&args(&["day3a", "--padding"])).is_err());
+    }
+
+    #[test]
+    fn numbers_too_large_are_errors() {
+        assert_eq!(Schematic::parse(b"..\n.123456789012345678901\n"), Err(NumberTooLarge { line: 2, column: 2 }));
+    }
+
+    #[test]
+    fn part_sums_too_large_are_errors() {
+        let schematic = Schematic::parse(b"18446744073709551615*\n1*\n").expect("Test - parse");
+        assert_eq!(schematic.part_sum(), Err(ValueTooLarge::PartSum { line: 2, column: 1 }));
+    }
+}
@@ -1,96 +1,119 @@
-use std::fs;
+use std::{env, fs::{self, File}, io::Write};
+
+use aoc::input::{self, StreamArgs};
+use day3::{render, rules, stream::{add_part_numbers, ScanError, WindowScanner}, Number, Schematic, Symbol};
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    if let Some(stream) = aoc::unwrap_or_exit(StreamArgs::from_args(&args, "input.txt")) {
+        let mut scanner = WindowScanner::parts(aoc::unwrap_or_exit(day3::padding_from_args(&args)));
+        let sum = stream.try_fold_lines(0, |sum, line| Ok::<u64, ScanError>(add_part_numbers(sum, &scanner.push(line)?)?))
+            .expect("Should have been able to read the input");
+        let events = aoc::unwrap_or_exit(scanner.finish());
+        print!("Sum of parts: {}", aoc::unwrap_or_exit(add_part_numbers(aoc::unwrap_or_exit(sum), &events)));
+        return;
+    }
+
     let contents = fs::read_to_string("input.txt")
         .expect("Something went wrong reading the file input.txt");
 
-    let parts_map = parse_input(contents)
-        .expect("Something went wrong parsing the input");
-
-    let mut sum = 0;
-    for (y, line) in parts_map.iter().enumerate() {
-        for (x, _) in line.iter().enumerate() {
-            sum += return_parts_num_value(x, y, &parts_map);
-        }
+    if args.iter().any(|arg| arg == "--compare-parsers") {
+        // Columns are bytes in the library and chars here, which only agree on ASCII
+        aoc::unwrap_or_exit(input::check_ascii(contents.as_bytes()));
+        input::compare_parsers(&contents, 100, |contents| parse_input(contents.to_string()), |contents| {
+            let schematic = aoc::unwrap_or_exit(Schematic::parse(contents));
+            (schematic.numbers, schematic.symbols)
+        });
+        return;
     }
 
-    print!("Sum of parts: {}", sum);
-}
+    let padding = aoc::unwrap_or_exit(day3::padding_from_args(&args));
+    let schematic = aoc::unwrap_or_exit(Schematic::parse_padded(contents.as_bytes(), padding));
 
-fn return_parts_num_value(x: usize, y: usize, map: &Vec<Vec<char>>) -> u32 {
-    if !map[y][x].is_ascii_digit() {
-        return 0;
+    if let Some(format) = aoc::unwrap_or_exit(render::format_from_args(&args)) {
+        let rule = aoc::unwrap_or_exit(rules::rule_from_args(&args));
+        print!("{}", render::render(&schematic, &rule, format, args.iter().any(|arg| arg == "--legend")));
+        return;
     }
 
-    if x > 0 && map[y][x-1].is_ascii_digit() {
-        return 0;
-    }
+    write_report_svg(&schematic).expect("Should have been able to write report.svg");
 
-    return part_value(x, y, &map)
+    print!("Sum of parts: {}", aoc::unwrap_or_exit(schematic.part_sum()));
 }
 
-/// Assumptions:
-/// - x and y are valid coordinates
-/// - x and y coordinates are the starting coordinates of the number
-fn part_value(x: usize, y: usize, map: &Vec<Vec<char>>) -> u32 {
-    let mut i = x;
-    let mut number: Vec<char> = Vec::new();
-
-    let mut is_adjacent = false;
-    while i < map[y].len() && map[y][i].is_ascii_digit() {
-        number.push(map[y][i]);
-        if are_adjacent_to_part(i, y, &map) {
-            is_adjacent = true;
-        }
-        i += 1;
+/// Marks every digit that belongs to a number adjacent to a part
+fn get_counted_digits(schematic: &Schematic) -> Vec<Vec<bool>> {
+    let mut counted: Vec<Vec<bool>> = schematic.rows.iter().map(|line| vec![false; line.len()]).collect();
+    for number in schematic.part_numbers() {
+        counted[number.row][number.start..number.end].fill(true);
     }
-
-    if !is_adjacent {
-        return 0;
-    }
-
-    let number_str: String = number.into_iter().collect();
-
-    number_str.parse::<u32>()
-        .expect("Not a valid number")
-}
-
-fn are_adjacent_to_part(x: usize, y: usize, map: &Vec<Vec<char>>) -> bool {
-    return x != 0 && is_part(x-1, y, &map)
-        || y != 0 && is_part(x, y-1, &map)
-        || is_part(x+1, y, map)
-        || is_part(x, y+1, map)
-        || x != 0 && y != 0 && is_part(x-1, y-1, map)
-        || is_part(x+1, y+1, map)
-        || x != 0 && is_part(x-1, y+1, map)
-        || y != 0 && is_part(x+1, y-1, map)
-}
-
-fn is_part(x: usize, y: usize, map: &Vec<Vec<char>>) -> bool {
-    // guards
-    if x >= map[0].len() || y >= map.len() {
-        return false;
-    }
-    return !map[y][x].is_ascii_digit() && map[y][x] != '.'
+    counted
 }
 
-fn print_map(map: Vec<Vec<char>>) {
-    for line in map {
-        for c in line {
-            print!("{}", c);
+const CELL_WIDTH: usize = 8;
+const CELL_HEIGHT: usize = 14;
+
+/// Draws the schematic with counted part numbers highlighted, for `aoc report`
+fn write_report_svg(schematic: &Schematic) -> std::io::Result<()> {
+    let map = &schematic.rows;
+    let counted = get_counted_digits(schematic);
+    let width = map.iter().map(|line| line.len()).max().unwrap_or(0);
+
+    let mut file = File::create("report.svg")?;
+    writeln!(file, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"monospace\" font-size=\"12\">",
+        width * CELL_WIDTH, map.len() * CELL_HEIGHT)?;
+    writeln!(file, "<rect width=\"100%\" height=\"100%\" fill=\"#0f0f23\"/>")?;
+
+    for (y, line) in map.iter().enumerate() {
+        for (x, &c) in line.iter().enumerate() {
+            let c = c as char;
+            let color = match c {
+                '.' => continue,
+                _ if counted[y][x] => "#ffff66",
+                c if c.is_ascii_digit() => "#666666",
+                _ => "#ff6666",
+            };
+
+            writeln!(file, "<text x=\"{}\" y=\"{}\" fill=\"{}\">{}</text>",
+                x * CELL_WIDTH, (y + 1) * CELL_HEIGHT - 3, color, aoc::escape_html(&c.to_string()))?;
         }
-        println!();
     }
+
+    writeln!(file, "</svg>")?;
+    Ok(())
 }
 
-fn parse_input(contents: String) -> Option<Vec<Vec<char>>> {
+/// The original char parser, kept as a reference for the numbers and symbols `Schematic::parse` finds
+fn parse_input(contents: String) -> (Vec<Number>, Vec<Symbol>) {
     // convert string to vector of vectors
     let mut vec: Vec<Vec<char>> = Vec::new();
     for line in contents.lines() {
         vec.push(line.chars().collect())
     }
 
-    Some(vec)
+    let mut numbers = Vec::new();
+    let mut symbols = Vec::new();
+    for (row, line) in vec.iter().enumerate() {
+        let mut x = 0;
+        while x < line.len() {
+            if !line[x].is_ascii_digit() {
+                if line[x] != '.' {
+                    symbols.push(Symbol { symbol: line[x], row, column: x });
+                }
+                x += 1;
+                continue;
+            }
+
+            let start = x;
+            while x < line.len() && line[x].is_ascii_digit() {
+                x += 1;
+            }
+            let value = line[start..x].iter().collect::<String>().parse::<u64>().expect("Not a valid number");
+            numbers.push(Number { value, row, start, end: x });
+        }
+    }
+    (numbers, symbols)
 }
 
 #[cfg(test)]
@@ -98,21 +121,23 @@ mod tests {
     use super::*;
 
     #[test]
-    fn parse_input_works() {
-        let contents = String::from("..123\n.....\n.#2..\n12...\n...@4");
-        let parts_map = parse_input(contents)
-            .expect("Something went wrong parsing the input");
-        assert_eq!(parts_map[0][2], '1');
-        assert_eq!(parts_map[1][0], '.');
+    fn parse_input_matches_schematic_parse() {
+        let contents = String::from("..123\n.....\n.#2..\n12...\n...@4\n");
+        let (numbers, symbols) = parse_input(contents.clone());
+        assert_eq!(numbers[0], Number { value: 123, row: 0, start: 2, end: 5 });
+        assert_eq!(symbols[1], Symbol { symbol: '@', row: 4, column: 3 });
+
+        let schematic = Schematic::parse(contents.as_bytes()).expect("Test - parse");
+        assert_eq!((schematic.numbers, schematic.symbols), (numbers, symbols));
     }
-    
+
     #[test]
-    fn is_number_adjacent_to_part_works()
-    {
-        let contents = String::from("..123\n.....\n.#2..\n12...\n...@4");
-        let parts_map = parse_input(contents)
-            .expect("Something went wrong parsing the input");
-        assert_eq!(part_value(2, 0, &parts_map), 0);
-        assert_eq!(part_value(2, 2, &parts_map), 2);
+    fn counted_digits_cover_whole_numbers() {
+        let schematic = Schematic::parse(b"..123\n.....\n.#2..\n12...\n...@4").expect("Test - parse");
+        let counted = get_counted_digits(&schematic);
+        assert_eq!(counted[0], vec![false; 5]);
+        assert_eq!(counted[2], vec![false, false, true, false, false]);
+        assert_eq!(counted[3], vec![true, true, false, false, false]);
+        assert_eq!(counted[4], vec![false, false, false, false, true]);
     }
 }
@@ -0,0 +1,345 @@
+//! Annotated schematic rendering, to see why a number was or wasn't counted.
+//!
+//! Part numbers are green, numbers without a symbol around them grey, symbols the rule matches (gears by default)
+//! yellow and every other symbol red. The HTML version is a standalone page whose cells have tooltips listing a
+//! number's symbols or a matched symbol's numbers and value.
+
+use std::{fmt::Write, ops::Range};
+
+use aoc::escape_html;
+
+use crate::{rules::SymbolRule, Schematic};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Ansi,
+    Html,
+}
+
+impl Format {
+    pub fn parse(text: &str) -> Result<Format, String> {
+        match text {
+            "ansi" => Ok(Format::Ansi),
+            "html" => Ok(Format::Html),
+            text => Err(format!("Unknown format `{}`, expected ansi or html", text)),
+        }
+    }
+}
+
+/// What a cell of the schematic shows
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Cell {
+    Empty,
+    /// Index into `numbers`
+    Number(usize),
+    /// Index into `symbols`
+    Symbol(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Class {
+    Part,
+    Unused,
+    Matched,
+    Symbol,
+}
+
+impl Class {
+    fn ansi(self) -> &'static str {
+        match self {
+            Class::Part => "\x1b[32m",
+            Class::Unused => "\x1b[90m",
+            Class::Matched => "\x1b[1;33m",
+            Class::Symbol => "\x1b[31m",
+        }
+    }
+
+    fn css(self) -> &'static str {
+        match self {
+            Class::Part => "part",
+            Class::Unused => "unused",
+            Class::Matched => "matched",
+            Class::Symbol => "symbol",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Class::Part => "part number",
+            Class::Unused => "number without a symbol",
+            Class::Matched => "symbol the rule matches",
+            Class::Symbol => "other symbol",
+        }
+    }
+}
+
+const LEGEND: [Class; 4] = [Class::Part, Class::Unused, Class::Matched, Class::Symbol];
+
+const STYLE: &str = "\
+body { background: #0f0f23; color: #cccccc; font-family: monospace; }
+pre { line-height: 1.2; }
+span[title] { cursor: help; }
+.part { color: #00cc00; }
+.unused { color: #666666; }
+.matched { color: #ffff66; font-weight: bold; }
+.symbol { color: #ff6666; }";
+
+/// A schematic with what the renderers need to know about each cell
+struct Annotated<'a> {
+    schematic: &'a Schematic,
+    cells: Vec<Vec<Cell>>,
+    /// The value of each symbol the rule matches, `None` inside if it doesn't fit in a u64
+    matched: Vec<Option<Option<u64>>>,
+}
+
+impl Annotated<'_> {
+    fn new<'a>(schematic: &'a Schematic, rule: &SymbolRule) -> Annotated<'a> {
+        let mut cells: Vec<Vec<Cell>> = schematic.rows.iter().map(|row| vec![Cell::Empty; row.len()]).collect();
+        for (i, number) in schematic.numbers.iter().enumerate() {
+            cells[number.row][number.start..number.end].fill(Cell::Number(i));
+        }
+        for (i, symbol) in schematic.symbols.iter().enumerate() {
+            cells[symbol.row][symbol.column] = Cell::Symbol(i);
+        }
+
+        let matched = (0..schematic.symbols.len())
+            .map(|i| schematic.rule_value(i, rule).transpose().map(|value| value.ok()))
+            .collect();
+
+        Annotated { schematic, cells, matched }
+    }
+
+    fn class(&self, cell: Cell) -> Option<Class> {
+        match cell {
+            Cell::Empty => None,
+            Cell::Number(i) if self.schematic.is_part_number(i) => Some(Class::Part),
+            Cell::Number(_) => Some(Class::Unused),
+            Cell::Symbol(i) if self.matched[i].is_some() => Some(Class::Matched),
+            Cell::Symbol(_) => Some(Class::Symbol),
+        }
+    }
+
+    /// The character `self.schematic.symbols[i]` is a byte of, read from the row since a character outside ASCII is a
+    /// symbol per byte, and the columns of its bytes
+    fn symbol_text(&self, i: usize) -> (String, Range<usize>) {
+        let symbol = &self.schematic.symbols[i];
+        let row = &self.schematic.rows[symbol.row];
+        let start = row[..=symbol.column].iter().rposition(|&b| !is_continuation(b)).unwrap_or(0);
+        let end = row[symbol.column + 1..].iter().position(|&b| !is_continuation(b)).map_or(row.len(), |length| symbol.column + 1 + length);
+        (String::from_utf8_lossy(&row[start..end]).into_owned(), start..end)
+    }
+
+    fn tooltip(&self, cell: Cell) -> String {
+        match cell {
+            Cell::Empty => String::new(),
+            Cell::Number(i) => {
+                let number = &self.schematic.numbers[i];
+                let mut symbols: Vec<String> = self.schematic.number_symbols[i].iter()
+                    .map(|&symbol| {
+                        let (text, columns) = self.symbol_text(symbol);
+                        format!("{} at {}:{}", text, self.schematic.symbols[symbol].row + 1, columns.start + 1)
+                    })
+                    .collect();
+                // Every byte of a character next to the number is a symbol of its own
+                symbols.dedup();
+                if self.schematic.is_padded(i) {
+                    symbols.push(String::from("the edge"));
+                }
+                if symbols.is_empty() {
+                    format!("{}: not counted, no symbol around it", number.value)
+                } else {
+                    format!("{}: counted, next to {}", number.value, symbols.join(", "))
+                }
+            },
+            Cell::Symbol(i) => {
+                // The numbers around any byte of the character
+                let (symbol, columns) = self.symbol_text(i);
+                let row = self.schematic.symbols[i].row;
+                let mut around: Vec<usize> = self.cells[row][columns].iter()
+                    .filter_map(|&cell| match cell {
+                        Cell::Symbol(j) => Some(j),
+                        _ => None,
+                    })
+                    .flat_map(|j| self.schematic.symbol_numbers[j].iter().copied())
+                    .collect();
+                around.sort();
+                around.dedup();
+                let numbers: Vec<String> = around.iter().map(|&number| self.schematic.numbers[number].value.to_string()).collect();
+                let numbers = if numbers.is_empty() { String::from("no numbers") } else { numbers.join(", ") };
+                match self.matched[i] {
+                    Some(Some(value)) => format!("{}: matched, next to {}, worth {}", symbol, numbers, value),
+                    Some(None) => format!("{}: matched, next to {}, worth more than a u64", symbol, numbers),
+                    None => format!("{}: next to {}", symbol, numbers),
+                }
+            },
+        }
+    }
+
+    /// The cells of a row grouped into runs that render the same way, a whole number or a single symbol. A symbol
+    /// outside ASCII renders as one character, in the run of its first byte.
+    fn runs(&self, row: usize) -> Vec<(Cell, String)> {
+        let mut runs: Vec<(Cell, Vec<u8>)> = Vec::new();
+        for (&cell, &b) in self.cells[row].iter().zip(&self.schematic.rows[row]) {
+            match runs.last_mut() {
+                Some((last, bytes)) if is_continuation(b) || *last == cell && matches!(cell, Cell::Empty | Cell::Number(_)) => bytes.push(b),
+                _ => runs.push((cell, vec![b])),
+            }
+        }
+        runs.into_iter().map(|(cell, bytes)| (cell, String::from_utf8_lossy(&bytes).into_owned())).collect()
+    }
+}
+
+/// Whether `b` continues a UTF-8 character rather than starting one
+fn is_continuation(b: u8) -> bool {
+    b & 0xc0 == 0x80
+}
+
+/// Renders the schematic with escape codes for a terminal. The legend lists the colors and every matched symbol.
+pub fn render_ansi(schematic: &Schematic, rule: &SymbolRule, legend: bool) -> String {
+    const RESET: &str = "\x1b[0m";
+
+    let annotated = Annotated::new(schematic, rule);
+    let mut text = String::new();
+    for row in 0..schematic.rows.len() {
+        for (cell, run) in annotated.runs(row) {
+            match annotated.class(cell) {
+                Some(class) => write!(text, "{}{}{}", class.ansi(), run, RESET).unwrap(),
+                None => text.push_str(&run),
+            }
+        }
+        text.push('\n');
+    }
+
+    if legend {
+        text.push('\n');
+        for class in LEGEND {
+            writeln!(text, "{}■{} {}", class.ansi(), RESET, class.description()).unwrap();
+        }
+        writeln!(text, "\nRule: {}", rule).unwrap();
+        for (i, symbol) in schematic.symbols.iter().enumerate() {
+            if annotated.matched[i].is_some() {
+                writeln!(text, "  {}:{} {}", symbol.row + 1, symbol.column + 1, annotated.tooltip(Cell::Symbol(i))).unwrap();
+            }
+        }
+    }
+
+    text
+}
+
+/// Renders the schematic as a standalone HTML page with a tooltip on every number and symbol
+pub fn render_html(schematic: &Schematic, rule: &SymbolRule, legend: bool) -> String {
+    let annotated = Annotated::new(schematic, rule);
+
+    let mut html = String::new();
+    writeln!(html, "<!DOCTYPE html>").unwrap();
+    writeln!(html, "<html><head><meta charset=\"utf-8\"><title>Engine schematic</title>").unwrap();
+    writeln!(html, "<style>{}</style></head><body>", STYLE).unwrap();
+
+    if legend {
+        writeln!(html, "<p>").unwrap();
+        for class in LEGEND {
+            writeln!(html, "<span class=\"{}\">■</span> {}<br>", class.css(), class.description()).unwrap();
+        }
+        writeln!(html, "Rule: {}</p>", escape_html(&rule.to_string())).unwrap();
+    }
+
+    write!(html, "<pre>").unwrap();
+    for row in 0..schematic.rows.len() {
+        for (cell, run) in annotated.runs(row) {
+            match annotated.class(cell) {
+                Some(class) => write!(html, "<span class=\"{}\" title=\"{}\">{}</span>",
+                    class.css(), escape_html(&annotated.tooltip(cell)), escape_html(&run)).unwrap(),
+                None => html.push_str(&escape_html(&run)),
+            }
+        }
+        html.push('\n');
+    }
+    writeln!(html, "</pre>").unwrap();
+
+    writeln!(html, "</body></html>").unwrap();
+    html
+}
+
+/// Renders in `format`, see [`render_ansi`] and [`render_html`]
+pub fn render(schematic: &Schematic, rule: &SymbolRule, format: Format, legend: bool) -> String {
+    match format {
+        Format::Ansi => render_ansi(schematic, rule, legend),
+        Format::Html => render_html(schematic, rule, legend),
+    }
+}
+
+/// Reads `--render <ansi|html>`, `None` if the schematic shouldn't be rendered
+pub fn format_from_args(args: &[String]) -> Result<Option<Format>, String> {
+    match args.iter().position(|arg| arg == "--render") {
+        Some(position) => {
+            let format = args.get(position + 1).ok_or(String::from("--render needs a format"))?;
+            Format::parse(format).map(Some)
+        },
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schematic() -> Schematic {
+        Schematic::parse(b"12*3.\n....7\n").expect("Test - parse")
+    }
+
+    #[test]
+    fn ansi_colors_numbers_and_symbols() {
+        let text = render_ansi(&schematic(), &SymbolRule::gear(), false);
+        assert_eq!(text, "\x1b[32m12\x1b[0m\x1b[1;33m*\x1b[0m\x1b[32m3\x1b[0m.\n....\x1b[90m7\x1b[0m\n");
+    }
+
+    #[test]
+    fn ansi_legend_lists_matched_symbols() {
+        let text = render_ansi(&schematic(), &SymbolRule::gear(), true);
+        assert!(text.contains("\x1b[90m■\x1b[0m number without a symbol\n"));
+        assert!(text.contains("Rule: * with exactly 2 numbers, product of the numbers\n  1:3 *: matched, next to 12, 3, worth 36\n"));
+    }
+
+    #[test]
+    fn unmatched_symbols_are_red() {
+        let rule = SymbolRule { neighbors: crate::rules::NeighborCount::Exactly(3), ..SymbolRule::gear() };
+        let text = render_ansi(&schematic(), &rule, false);
+        assert!(text.starts_with("\x1b[32m12\x1b[0m\x1b[31m*\x1b[0m"));
+    }
+
+    #[test]
+    fn html_has_tooltips() {
+        let schematic = Schematic::parse(b"1<2..\n....5\n").expect("Test - parse");
+        let html = render_html(&schematic, &SymbolRule::gear(), true);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<span class=\"part\" title=\"1: counted, next to &lt; at 1:2\">1</span>"));
+        assert!(html.contains("<span class=\"symbol\" title=\"&lt;: next to 1, 2\">&lt;</span>"));
+        assert!(html.contains("....<span class=\"unused\" title=\"5: not counted, no symbol around it\">5</span>"));
+        assert!(html.contains("Rule: * with exactly 2 numbers"));
+        assert!(!render_html(&schematic, &SymbolRule::gear(), false).contains("Rule:"));
+    }
+
+    #[test]
+    fn symbols_outside_ascii_render_as_one_character() {
+        let schematic = Schematic::parse("1é2\n".as_bytes()).expect("Test - parse");
+        let html = render_html(&schematic, &SymbolRule::gear(), false);
+        assert!(html.contains("<span class=\"part\" title=\"1: counted, next to é at 1:2\">1</span>"));
+        assert!(html.contains("<span class=\"symbol\" title=\"é: next to 1, 2\">é</span>"));
+        assert_eq!(render_ansi(&schematic, &SymbolRule::gear(), false), "\x1b[32m1\x1b[0m\x1b[31mé\x1b[0m\x1b[32m2\x1b[0m\n");
+    }
+
+    #[test]
+    fn padding_shows_in_tooltips() {
+        let schematic = Schematic::parse_padded(b"1.\n..\n", b'#').expect("Test - parse");
+        let html = render_html(&schematic, &SymbolRule::gear(), false);
+        assert!(html.contains("<span class=\"part\" title=\"1: counted, next to the edge\">1</span>"));
+    }
+
+    #[test]
+    fn format_from_args_works() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<String>>();
+        assert_eq!(format_from_args(&args(&["day3a"])), Ok(None));
+        assert_eq!(format_from_args(&args(&["day3a", "--render", "html"])), Ok(Some(Format::Html)));
+        assert_eq!(format_from_args(&args(&["day3a", "--render", "svg"])), Err(String::from("Unknown format `svg`, expected ansi or html")));
+    }
+}
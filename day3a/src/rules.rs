@@ -0,0 +1,306 @@
+//! Configurable rules for which symbols count and what they're worth.
+//!
+//! Part 2's gear is one rule: a `*` with exactly two numbers around it, worth their product. Others can be given to
+//! day3b on the command line, e.g. the sum of the numbers around every `#`:
+//!
+//! ```text
+//! --symbols '#' --neighbors min:1 --reduce sum
+//! ```
+//!
+//! or the product of the numbers around a `$` that has three of them with `--symbols '$' --neighbors 3`.
+
+use std::fmt;
+
+use crate::{Number, Schematic, Symbol};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SymbolSet {
+    Any,
+    Only(Vec<char>),
+}
+
+impl SymbolSet {
+    pub fn contains(&self, symbol: char) -> bool {
+        match self {
+            SymbolSet::Any => true,
+            SymbolSet::Only(symbols) => symbols.contains(&symbol),
+        }
+    }
+}
+
+/// How many numbers a symbol needs around it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NeighborCount {
+    Exactly(usize),
+    AtLeast(usize),
+    AtMost(usize),
+}
+
+impl NeighborCount {
+    /// Parses `<n>` or `exact:<n>`, `min:<n>` and `max:<n>`
+    pub fn parse(text: &str) -> Result<NeighborCount, String> {
+        let (kind, count) = text.split_once(':').unwrap_or(("exact", text));
+        let count = count.parse::<usize>().map_err(|_| format!("`{}` is not a number of neighbors", count))?;
+        match kind {
+            "exact" => Ok(NeighborCount::Exactly(count)),
+            "min" => Ok(NeighborCount::AtLeast(count)),
+            "max" => Ok(NeighborCount::AtMost(count)),
+            kind => Err(format!("Unknown neighbor count `{}`, expected exact, min or max", kind)),
+        }
+    }
+
+    pub fn allows(&self, neighbors: usize) -> bool {
+        match *self {
+            NeighborCount::Exactly(count) => neighbors == count,
+            NeighborCount::AtLeast(count) => neighbors >= count,
+            NeighborCount::AtMost(count) => neighbors <= count,
+        }
+    }
+}
+
+/// How the numbers around a matching symbol are combined into its value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reducer {
+    Product,
+    Sum,
+    Max,
+}
+
+impl Reducer {
+    pub fn parse(text: &str) -> Result<Reducer, String> {
+        match text {
+            "product" => Ok(Reducer::Product),
+            "sum" => Ok(Reducer::Sum),
+            "max" => Ok(Reducer::Max),
+            text => Err(format!("Unknown reducer `{}`, expected product, sum or max", text)),
+        }
+    }
+
+    /// The numbers combined, `None` if that doesn't fit in a u64
+    pub fn reduce<'a>(&self, numbers: impl Iterator<Item = &'a Number>) -> Option<u64> {
+        let mut values = numbers.map(|number| number.value);
+        match self {
+            Reducer::Product => values.try_fold(1u64, |product, value| product.checked_mul(value)),
+            Reducer::Sum => values.try_fold(0u64, |sum, value| sum.checked_add(value)),
+            Reducer::Max => Some(values.max().unwrap_or(0)),
+        }
+    }
+}
+
+/// A symbol whose value under a rule doesn't fit in a u64, with the symbol's 1-based position
+#[derive(Debug, PartialEq)]
+pub enum ValueTooLarge {
+    /// The numbers around the symbol combine into more than a u64
+    Symbol { line: usize, column: usize },
+    /// Adding the symbol's value takes the sum of the values past a u64
+    Sum { line: usize, column: usize },
+    /// Adding the part number at this position takes the sum of the part numbers past a u64
+    PartSum { line: usize, column: usize },
+}
+
+impl ValueTooLarge {
+    fn symbol(symbol: &Symbol) -> ValueTooLarge {
+        ValueTooLarge::Symbol { line: symbol.row + 1, column: symbol.column + 1 }
+    }
+
+    pub(crate) fn sum(symbol: &Symbol) -> ValueTooLarge {
+        ValueTooLarge::Sum { line: symbol.row + 1, column: symbol.column + 1 }
+    }
+
+    pub(crate) fn part_sum(number: &Number) -> ValueTooLarge {
+        ValueTooLarge::PartSum { line: number.row + 1, column: number.start + 1 }
+    }
+}
+
+impl fmt::Display for ValueTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValueTooLarge::Symbol { line, column } =>
+                write!(f, "Line {}: the value of the symbol at column {} doesn't fit in a u64", line, column),
+            ValueTooLarge::Sum { line, column } =>
+                write!(f, "Line {}: adding the value of the symbol at column {} takes the sum past a u64", line, column),
+            ValueTooLarge::PartSum { line, column } =>
+                write!(f, "Line {}: adding the part number at column {} takes the sum past a u64", line, column),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolRule {
+    pub symbols: SymbolSet,
+    pub neighbors: NeighborCount,
+    pub reducer: Reducer,
+}
+
+impl SymbolRule {
+    /// Part 2's rule: a `*` next to exactly two numbers, worth their product
+    pub fn gear() -> SymbolRule {
+        SymbolRule { symbols: SymbolSet::Only(vec!['*']), neighbors: NeighborCount::Exactly(2), reducer: Reducer::Product }
+    }
+
+    /// Whether the rule matches `symbol` with `neighbors` numbers around it.
+    /// A symbol without any numbers around it never matches.
+    pub fn matches(&self, symbol: char, neighbors: usize) -> bool {
+        self.symbols.contains(symbol) && neighbors > 0 && self.neighbors.allows(neighbors)
+    }
+
+    /// The value of `symbol` with `numbers` around it, `None` if the rule doesn't match it
+    pub fn value(&self, symbol: &Symbol, numbers: &[&Number]) -> Result<Option<u64>, ValueTooLarge> {
+        if !self.matches(symbol.symbol, numbers.len()) {
+            return Ok(None);
+        }
+        self.reducer.reduce(numbers.iter().copied()).map(Some).ok_or_else(|| ValueTooLarge::symbol(symbol))
+    }
+}
+
+impl fmt::Display for SymbolRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.symbols {
+            SymbolSet::Any => write!(f, "any symbol")?,
+            SymbolSet::Only(symbols) => write!(f, "{}", symbols.iter().collect::<String>())?,
+        }
+        let (bound, count) = match self.neighbors {
+            NeighborCount::Exactly(count) => ("exactly", count),
+            NeighborCount::AtLeast(count) => ("at least", count),
+            NeighborCount::AtMost(count) => ("at most", count),
+        };
+        write!(f, " with {} {} {}", bound, count, if count == 1 { "number" } else { "numbers" })?;
+        let reducer = match self.reducer {
+            Reducer::Product => "product",
+            Reducer::Sum => "sum",
+            Reducer::Max => "max",
+        };
+        write!(f, ", {} of the numbers", reducer)
+    }
+}
+
+/// Reads a rule from `--symbols <chars|any>`, `--neighbors <count>` and `--reduce <reducer>`. Each one that isn't
+/// given is taken from the gear rule.
+pub fn rule_from_args(args: &[String]) -> Result<SymbolRule, String> {
+    let get_option = |name: &str| args.iter()
+        .position(|arg| arg == name)
+        .map(|position| args.get(position + 1).map(|value| value.as_str()).ok_or(format!("{} needs a value", name)));
+
+    let mut rule = SymbolRule::gear();
+    if let Some(symbols) = get_option("--symbols") {
+        rule.symbols = match symbols? {
+            "any" => SymbolSet::Any,
+            "" => return Err(String::from("--symbols needs at least one symbol")),
+            symbols => SymbolSet::Only(symbols.chars().collect()),
+        };
+    }
+    if let Some(neighbors) = get_option("--neighbors") {
+        rule.neighbors = NeighborCount::parse(neighbors?)?;
+    }
+    if let Some(reducer) = get_option("--reduce") {
+        rule.reducer = Reducer::parse(reducer?)?;
+    }
+    Ok(rule)
+}
+
+impl Schematic {
+    /// The value of `self.symbols[symbol]` under the rule, `None` if the rule doesn't match it
+    pub fn rule_value(&self, symbol: usize, rule: &SymbolRule) -> Result<Option<u64>, ValueTooLarge> {
+        let numbers: Vec<&Number> = self.numbers_next_to(symbol).collect();
+        rule.value(&self.symbols[symbol], &numbers)
+    }
+
+    /// Every symbol the rule matches, with its value
+    pub fn matches<'a>(&'a self, rule: &'a SymbolRule) -> impl Iterator<Item = Result<(&'a Symbol, u64), ValueTooLarge>> {
+        self.symbols.iter()
+            .enumerate()
+            .filter_map(|(i, symbol)| self.rule_value(i, rule).map(|value| Some((symbol, value?))).transpose())
+    }
+
+    /// The sum of the values of every symbol the rule matches
+    pub fn rule_sum(&self, rule: &SymbolRule) -> Result<u64, ValueTooLarge> {
+        self.matches(rule).try_fold(0u64, |sum, matched| {
+            let (symbol, value) = matched?;
+            sum.checked_add(value).ok_or_else(|| ValueTooLarge::sum(symbol))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &[u8] = b"\
+467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..
+";
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn gear_rule_is_part_2() {
+        let schematic = Schematic::parse(EXAMPLE).expect("Test - parse");
+        let gear_ratios: u64 = schematic.gears().map(|(_, a, b)| a.value * b.value).sum();
+        assert_eq!(schematic.rule_sum(&SymbolRule::gear()), Ok(gear_ratios));
+        assert_eq!(rule_from_args(&args(&["day3b"])), Ok(SymbolRule::gear()));
+    }
+
+    #[test]
+    fn other_rules() {
+        let schematic = Schematic::parse(EXAMPLE).expect("Test - parse");
+
+        let hash_sum = rule_from_args(&args(&["--symbols", "#", "--neighbors", "min:1", "--reduce", "sum"]))
+            .expect("Test - rule");
+        assert_eq!(schematic.rule_sum(&hash_sum), Ok(633));
+
+        // Every symbol but the two gears has a single number around it
+        let lonely = rule_from_args(&args(&["--symbols", "any", "--neighbors", "max:1", "--reduce", "max"]))
+            .expect("Test - rule");
+        assert_eq!(schematic.rule_sum(&lonely), Ok(633 + 617 + 592 + 664));
+
+        let stars = rule_from_args(&args(&["--symbols", "*$", "--neighbors", "exact:1"])).expect("Test - rule");
+        let symbols: Result<String, ValueTooLarge> = schematic.matches(&stars).map(|matched| Ok(matched?.0.symbol)).collect();
+        assert_eq!(symbols, Ok(String::from("*$")));
+    }
+
+    #[test]
+    fn symbols_without_numbers_never_match() {
+        let schematic = Schematic::parse(b"*...\n..1*\n").expect("Test - parse");
+        let rule = SymbolRule { symbols: SymbolSet::Any, neighbors: NeighborCount::AtMost(2), reducer: Reducer::Product };
+        assert_eq!(schematic.matches(&rule).count(), 1);
+        assert_eq!(schematic.rule_sum(&rule), Ok(1));
+    }
+
+    #[test]
+    fn values_too_large_are_errors() {
+        let schematic = Schematic::parse(b"99999999999*99999999999\n").expect("Test - parse");
+        assert_eq!(schematic.rule_sum(&SymbolRule::gear()), Err(ValueTooLarge::Symbol { line: 1, column: 12 }));
+
+        let sum = SymbolRule { symbols: SymbolSet::Any, neighbors: NeighborCount::AtLeast(1), reducer: Reducer::Sum };
+        let schematic = Schematic::parse(b"18446744073709551615*\n......................#1\n").expect("Test - parse");
+        assert_eq!(schematic.rule_sum(&sum), Err(ValueTooLarge::Sum { line: 2, column: 23 }));
+        assert_eq!(schematic.rule_sum(&sum).map_err(|error| error.to_string()),
+            Err(String::from("Line 2: adding the value of the symbol at column 23 takes the sum past a u64")));
+    }
+
+    #[test]
+    fn bad_rules_are_errors() {
+        assert_eq!(NeighborCount::parse("2"), Ok(NeighborCount::Exactly(2)));
+        assert_eq!(NeighborCount::parse("about:2"), Err(String::from("Unknown neighbor count `about`, expected exact, min or max")));
+        assert_eq!(NeighborCount::parse("min:two"), Err(String::from("`two` is not a number of neighbors")));
+        assert_eq!(rule_from_args(&args(&["--reduce", "mean"])), Err(String::from("Unknown reducer `mean`, expected product, sum or max")));
+        assert_eq!(rule_from_args(&args(&["--symbols"])), Err(String::from("--symbols needs a value")));
+    }
+
+    #[test]
+    fn rules_display() {
+        assert_eq!(SymbolRule::gear().to_string(), "* with exactly 2 numbers, product of the numbers");
+        let rule = SymbolRule { symbols: SymbolSet::Any, neighbors: NeighborCount::AtLeast(1), reducer: Reducer::Sum };
+        assert_eq!(rule.to_string(), "any symbol with at least 1 number, sum of the numbers");
+    }
+}
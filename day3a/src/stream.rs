@@ -0,0 +1,353 @@
+//! Scans a schematic one row at a time, for inputs too large to hold in memory.
+//!
+//! Whether a number or symbol on a row counts only depends on the rows just above and below it, so
+//! [`WindowScanner`] keeps the previous, current and next rows and reports a row's part numbers and matched symbols
+//! as soon as the row after it arrives. The answers are the same as [`Schematic`](crate::Schematic)'s, padding
+//! included.
+
+use std::{collections::VecDeque, fmt};
+
+use crate::{is_symbol, parse_row, rules::{SymbolRule, ValueTooLarge}, Number, NumberTooLarge, Symbol};
+
+/// Why a row couldn't be scanned
+#[derive(Debug, PartialEq)]
+pub enum ScanError {
+    Number(NumberTooLarge),
+    Value(ValueTooLarge),
+}
+
+impl From<NumberTooLarge> for ScanError {
+    fn from(error: NumberTooLarge) -> ScanError {
+        ScanError::Number(error)
+    }
+}
+
+impl From<ValueTooLarge> for ScanError {
+    fn from(error: ValueTooLarge) -> ScanError {
+        ScanError::Value(error)
+    }
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScanError::Number(error) => error.fmt(f),
+            ScanError::Value(error) => error.fmt(f),
+        }
+    }
+}
+
+/// Something that counts, reported once its row has scrolled past
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    Part(Number),
+    /// A symbol the rule matches, with its value
+    Match(Symbol, u64),
+}
+
+/// The running answers of both parts
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Totals {
+    pub part_sum: u64,
+    pub rule_sum: u64,
+}
+
+impl Totals {
+    /// Adds what the events count, failing like [`Schematic::part_sum`](crate::Schematic::part_sum) and
+    /// [`Schematic::rule_sum`](crate::Schematic::rule_sum) if either sum passes a u64
+    pub fn add(&mut self, events: &[Event]) -> Result<(), ValueTooLarge> {
+        self.part_sum = add_part_numbers(self.part_sum, events)?;
+        self.rule_sum = add_rule_values(self.rule_sum, events)?;
+        Ok(())
+    }
+}
+
+/// Adds the part numbers in `events` to `sum`, with the error at the number that takes it past a u64
+pub fn add_part_numbers(sum: u64, events: &[Event]) -> Result<u64, ValueTooLarge> {
+    events.iter().try_fold(sum, |sum, event| match event {
+        Event::Part(number) => sum.checked_add(number.value).ok_or_else(|| ValueTooLarge::part_sum(number)),
+        Event::Match(..) => Ok(sum),
+    })
+}
+
+/// Adds the values of the matched symbols in `events` to `sum`, with the error at the symbol that takes it past a u64
+pub fn add_rule_values(sum: u64, events: &[Event]) -> Result<u64, ValueTooLarge> {
+    events.iter().try_fold(sum, |sum, event| match event {
+        Event::Part(_) => Ok(sum),
+        Event::Match(symbol, value) => sum.checked_add(*value).ok_or_else(|| ValueTooLarge::sum(symbol)),
+    })
+}
+
+struct Row {
+    bytes: Vec<u8>,
+    numbers: Vec<Number>,
+    symbols: Vec<Symbol>,
+}
+
+pub struct WindowScanner<'a> {
+    /// `None` when only part numbers are wanted, so no symbol's value is ever worked out
+    rule: Option<&'a SymbolRule>,
+    padding_is_symbol: bool,
+    /// At most three rows: the one before the row being decided, that row and the one after it
+    window: VecDeque<Row>,
+    /// Index of the next row pushed
+    next_row: usize,
+}
+
+impl WindowScanner<'_> {
+    pub fn new(rule: &SymbolRule, padding: u8) -> WindowScanner<'_> {
+        WindowScanner { rule: Some(rule), padding_is_symbol: is_symbol(padding), window: VecDeque::new(), next_row: 0 }
+    }
+
+    /// A scanner that only reports part numbers, for part 1
+    pub fn parts(padding: u8) -> WindowScanner<'static> {
+        WindowScanner { rule: None, padding_is_symbol: is_symbol(padding), window: VecDeque::new(), next_row: 0 }
+    }
+
+    /// Adds the next row and returns what counts on the row before it, which can now be decided
+    pub fn push(&mut self, line: &[u8]) -> Result<Vec<Event>, ScanError> {
+        let (numbers, symbols) = parse_row(self.next_row, line)?;
+        self.window.push_back(Row { bytes: line.to_vec(), numbers, symbols });
+        self.next_row += 1;
+
+        if self.window.len() == 1 {
+            return Ok(Vec::new());
+        }
+
+        let events = match self.window.len() {
+            2 => self.decide(None, &self.window[0], Some(&self.window[1]))?,
+            _ => self.decide(Some(&self.window[0]), &self.window[1], Some(&self.window[2]))?,
+        };
+        if self.window.len() == 3 {
+            self.window.pop_front();
+        }
+        Ok(events)
+    }
+
+    /// Returns what counts on the last row, which has nothing below it
+    pub fn finish(self) -> Result<Vec<Event>, ValueTooLarge> {
+        let Some(current) = self.window.back() else {
+            return Ok(Vec::new());
+        };
+        let previous = self.window.len().checked_sub(2).map(|i| &self.window[i]);
+        self.decide(previous, current, None)
+    }
+
+    /// The part numbers and matched symbols of `current`, given the rows around it (`None` past the edges)
+    fn decide(&self, previous: Option<&Row>, current: &Row, next: Option<&Row>) -> Result<Vec<Event>, ValueTooLarge> {
+        let rows = [previous, Some(current), next];
+        let mut events = Vec::new();
+
+        for number in &current.numbers {
+            // Left of the first column is outside the schematic
+            let mut is_part = self.padding_is_symbol && number.start == 0;
+            for row in rows {
+                let Some(row) = row else {
+                    is_part |= self.padding_is_symbol;
+                    continue;
+                };
+                for column in number.start.saturating_sub(1)..=number.end {
+                    is_part |= match row.bytes.get(column) {
+                        Some(&b) => is_symbol(b),
+                        None => self.padding_is_symbol,
+                    };
+                }
+            }
+
+            if is_part {
+                events.push(Event::Part(number.clone()));
+            }
+        }
+
+        let Some(rule) = self.rule else {
+            return Ok(events);
+        };
+        for symbol in &current.symbols {
+            let numbers: Vec<&Number> = rows.iter()
+                .flatten()
+                .flat_map(|row| &row.numbers)
+                .filter(|number| number.start <= symbol.column + 1 && number.end >= symbol.column)
+                .collect();
+            if let Some(value) = rule.value(symbol, &numbers)? {
+                events.push(Event::Match(symbol.clone(), value));
+            }
+        }
+
+        Ok(events)
+    }
+}
+
+/// Scans the whole input with a [`WindowScanner`]
+pub fn scan(contents: &[u8], rule: &SymbolRule, padding: u8) -> Result<Totals, ScanError> {
+    let mut scanner = WindowScanner::new(rule, padding);
+    let mut totals = Totals::default();
+    for line in aoc::input::lines(contents) {
+        totals.add(&scanner.push(line)?)?;
+    }
+    totals.add(&scanner.finish()?)?;
+    Ok(totals)
+}
+
+/// Sums the part numbers of the whole input with [`WindowScanner::parts`]
+pub fn scan_parts(contents: &[u8], padding: u8) -> Result<u64, ScanError> {
+    let mut scanner = WindowScanner::parts(padding);
+    let mut sum = 0;
+    for line in aoc::input::lines(contents) {
+        sum = add_part_numbers(sum, &scanner.push(line)?)?;
+    }
+    Ok(add_part_numbers(sum, &scanner.finish()?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc::testing::{check_generated, Rng};
+    use crate::{rules::{NeighborCount, Reducer, SymbolSet}, Schematic};
+
+    const EXAMPLE: &[u8] = b"\
+467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..
+";
+
+    /// Checks the scanner against the full schematic
+    fn assert_same_answers(contents: &[u8], rule: &SymbolRule, padding: u8) {
+        let schematic = Schematic::parse_padded(contents, padding).expect("Test - parse");
+        let expected = Totals { part_sum: schematic.part_sum().expect("Test - part sum"), rule_sum: schematic.rule_sum(rule).expect("Test - rule sum") };
+        assert_eq!(scan(contents, rule, padding), Ok(expected), "{:?}", String::from_utf8_lossy(contents));
+    }
+
+    #[test]
+    fn example_answers() {
+        assert_eq!(scan(EXAMPLE, &SymbolRule::gear(), b'.'), Ok(Totals { part_sum: 4361, rule_sum: 467835 }));
+    }
+
+    #[test]
+    fn events_arrive_a_row_late() {
+        let rule = SymbolRule::gear();
+        let mut scanner = WindowScanner::new(&rule, b'.');
+        assert_eq!(scanner.push(b"12*3"), Ok(Vec::new()));
+
+        let events = scanner.push(b"....").expect("Test - push");
+        let values: Vec<u64> = events.iter()
+            .map(|event| match event {
+                Event::Part(number) => number.value,
+                Event::Match(_, value) => *value,
+            })
+            .collect();
+        assert_eq!(values, vec![12, 3, 36]);
+        assert_eq!(scanner.finish(), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn matches_the_schematic() {
+        let inputs: [&[u8]; 7] = [
+            EXAMPLE,
+            b"",
+            b"5\n",
+            b"1*1\n",
+            b"12\n..*.\n\n3\n.....7\n99\n",
+            b"1...5...2\n.#.....#.\n6.......7\n.#.....#.\n3...8...4\n",
+            b"2.\n*3\n.4\n*.\n5\n",
+        ];
+        let rules = [
+            SymbolRule::gear(),
+            SymbolRule { symbols: SymbolSet::Any, neighbors: NeighborCount::AtLeast(1), reducer: Reducer::Sum },
+            SymbolRule { symbols: SymbolSet::Only(vec!['#', '*']), neighbors: NeighborCount::AtMost(2), reducer: Reducer::Max },
+        ];
+
+        for contents in inputs {
+            for rule in &rules {
+                for padding in [b'.', b'#', b'*'] {
+                    assert_same_answers(contents, rule, padding);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn matches_the_schematic_on_generated_input() {
+        // Ragged grids of digits, dots and a few symbols
+        let generate = |rng: &mut Rng, _| {
+            let mut contents = String::new();
+            for _ in 0..12 {
+                for _ in 0..10 + rng.below(20) {
+                    contents.push(match rng.below(10) {
+                        0..=3 => char::from(b'0' + rng.below(10) as u8),
+                        4 => char::from(b"*#$+"[rng.below(4) as usize]),
+                        _ => '.',
+                    });
+                }
+                contents.push('\n');
+            }
+            contents
+        };
+
+        let rule = SymbolRule { symbols: SymbolSet::Any, neighbors: NeighborCount::AtLeast(2), reducer: Reducer::Sum };
+        check_generated(2023, 10, generate, |contents| {
+            for padding in [b'.', b'#'] {
+                assert_same_answers(contents.as_bytes(), &SymbolRule::gear(), padding);
+                assert_same_answers(contents.as_bytes(), &rule, padding);
+            }
+        });
+    }
+
+    #[test]
+    fn numbers_too_large_are_errors() {
+        let rule = SymbolRule::gear();
+        assert_eq!(scan(b"1\n123456789012345678901\n", &rule, b'.'), Err(ScanError::Number(NumberTooLarge { line: 2, column: 1 })));
+        assert_eq!(scan(b"99999999999*99999999999\n", &rule, b'.'), Err(ScanError::Value(ValueTooLarge::Symbol { line: 1, column: 12 })));
+    }
+
+    #[test]
+    fn sums_too_large_are_errors() {
+        let contents = b"18446744073709551615*\n......................#1\n";
+        let rule = SymbolRule { symbols: SymbolSet::Any, neighbors: NeighborCount::AtLeast(1), reducer: Reducer::Sum };
+        let schematic = Schematic::parse(contents).expect("Test - parse");
+
+        // Both numbers are part numbers too, so only the rule's sum is streamed, as day3b does, and the part sum
+        // overflows first in `scan`
+        assert_eq!(scan(contents, &rule, b'.'), Err(ScanError::Value(ValueTooLarge::PartSum { line: 2, column: 24 })));
+        let mut scanner = WindowScanner::new(&rule, b'.');
+        let mut sum = Ok(0);
+        for line in aoc::input::lines(contents) {
+            let events = scanner.push(line).expect("Test - push");
+            sum = sum.and_then(|sum| add_rule_values(sum, &events));
+        }
+        let events = scanner.finish().expect("Test - finish");
+        sum = sum.and_then(|sum| add_rule_values(sum, &events));
+
+        assert_eq!(schematic.rule_sum(&rule), Err(ValueTooLarge::Sum { line: 2, column: 23 }));
+        assert_eq!(sum, schematic.rule_sum(&rule));
+    }
+
+    #[test]
+    fn part_sums_too_large_are_errors() {
+        let contents = b"18446744073709551615*\n1*\n";
+        let schematic = Schematic::parse(contents).expect("Test - parse");
+        assert_eq!(scan_parts(contents, b'.'), schematic.part_sum().map_err(ScanError::from));
+        assert_eq!(scan_parts(contents, b'.'), Err(ScanError::Value(ValueTooLarge::PartSum { line: 2, column: 1 })));
+    }
+
+    #[test]
+    fn parts_never_work_out_rule_values() {
+        let contents = b"99999999999*99999999999\n";
+        let schematic = Schematic::parse(contents).expect("Test - parse");
+        assert_eq!(scan_parts(contents, b'.'), schematic.part_sum().map_err(ScanError::from));
+        assert_eq!(scan_parts(contents, b'.'), Ok(199999999998));
+
+        for contents in [EXAMPLE, b"", b"5\n", b"2.\n*3\n.4\n*.\n5\n"] {
+            for padding in [b'.', b'#'] {
+                let schematic = Schematic::parse_padded(contents, padding).expect("Test - parse");
+                assert_eq!(scan_parts(contents, padding), schematic.part_sum().map_err(ScanError::from));
+            }
+        }
+    }
+}
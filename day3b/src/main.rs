@@ -1,134 +1,84 @@
-use std::fs;
-use std::collections::HashSet;
-
-const DIRECTIONS : [[i32; 2]; 8]= [
-    [0, 1],
-    [1, 0],
-    [0, -1],
-    [-1, 0],
-    [1, 1],
-    [1, -1],
-    [-1, 1],
-    [-1, -1],
-];
+use std::{env, fs, process};
 
-fn main() {
-    let input = fs::read_to_string("input.txt")
-        .expect("Should have read input.txt");
-
-    let map = parse(input);
-
-    let sum = calculate_answer(map);
-
-    println!("Sum: {}", sum);
-    println!("");
-}
+use aoc::input::{self, StreamArgs};
+use day3::{render, rules::{self, SymbolRule}, stream::{add_rule_values, ScanError, WindowScanner}, Number, Schematic, Symbol};
 
-fn calculate_answer(map: Vec<Vec<char>>) -> u32 {
-    let mut sum = 0;
-    for (y, line) in map.iter().enumerate() {
-        for (x, c) in line.iter().enumerate() {
-            if *c == '*' {
-                sum += calculate_gear_ratio(x, y, &map);
-            }
-        }
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let rule = aoc::unwrap_or_exit(rules::rule_from_args(&args));
+    let render_format = aoc::unwrap_or_exit(render::format_from_args(&args));
+
+    // The padding is never a gear, so it only shows in the rendered schematic's part numbers
+    if render_format.is_none() && args.iter().any(|arg| arg == "--padding") {
+        eprintln!("--padding can't change day3b's answer, it only applies with --render");
+        process::exit(1);
     }
-    sum
-}
 
-fn parse(input: String) -> Vec<Vec<char>> {
-    let map : Vec<Vec<char>> = input.lines()
-        .map(|line| line.chars().collect::<Vec<char>>())
-        .collect();
-    map
-}
+    if let Some(stream) = aoc::unwrap_or_exit(StreamArgs::from_args(&args, "input.txt")) {
+        let mut scanner = WindowScanner::new(&rule, day3::DEFAULT_PADDING);
+        let sum = stream.try_fold_lines(0, |sum, line| Ok::<u64, ScanError>(add_rule_values(sum, &scanner.push(line)?)?))
+            .expect("Should have been able to read the input");
+        let events = aoc::unwrap_or_exit(scanner.finish());
+        println!("Sum: {}", aoc::unwrap_or_exit(add_rule_values(aoc::unwrap_or_exit(sum), &events)));
+        return;
+    }
 
-fn calculate_gear_ratio(x: usize, y: usize, map: &Vec<Vec<char>>) -> u32
-{
-    // look for adjacent numbers
-    let mut adjacent_numbers: HashSet<Vec<(usize, usize)>> = HashSet::new();
-    
-    for direction in DIRECTIONS.iter() {
-        let i = x as i32 + direction[0];
-        let j = y as i32 + direction[1];
-
-        if i < 0 || j < 0 {
-            continue;
-        }
-        
-        if j >= map.len() as i32 || i >= map[j as usize].len() as i32 {
-            continue;
-        }
+    let input = fs::read_to_string("input.txt")
+        .expect("Should have read input.txt");
 
-        if map[j as usize][i as usize].is_numeric() {
-            adjacent_numbers.insert(get_number(i as usize, j as usize, &map));
-        }
+    if args.iter().any(|arg| arg == "--compare-parsers") {
+        // Columns are bytes in the library and chars here, which only agree on ASCII
+        aoc::unwrap_or_exit(input::check_ascii(input.as_bytes()));
+        input::compare_parsers(&input, 100, |input| parse(input.to_string()), |input| {
+            let schematic = aoc::unwrap_or_exit(Schematic::parse(input));
+            (schematic.numbers, schematic.symbols)
+        });
+        return;
     }
 
-    if adjacent_numbers.len() != 2 {
-        return 0;
+    if let Some(format) = render_format {
+        let padding = aoc::unwrap_or_exit(day3::padding_from_args(&args));
+        let schematic = aoc::unwrap_or_exit(Schematic::parse_padded(input.as_bytes(), padding));
+        print!("{}", render::render(&schematic, &rule, format, args.iter().any(|arg| arg == "--legend")));
+        return;
     }
 
-    let mut iter = adjacent_numbers.iter();
-    let first_number = iter.next()
-        .expect("First number");
-    let second_number = iter.next()
-        .expect("Second number");
-
-        get_value(first_number, &map) * get_value(second_number, &map)
-}
+    let schematic = aoc::unwrap_or_exit(Schematic::parse(input.as_bytes()));
 
-fn get_value(number: &Vec<(usize, usize)>, map: &Vec<Vec<char>>) -> u32 {
-    let mut value = 0;
-    for (x, y) in number {
-        value *= 10;
-        value += map[*y][*x].to_digit(10).expect("Digit");
+    if rule != SymbolRule::gear() {
+        println!("Rule: {}", rule);
     }
-    value
+    println!("Sum: {}", aoc::unwrap_or_exit(schematic.rule_sum(&rule)));
+    println!();
 }
 
-fn get_number(x: usize, y: usize, map: &Vec<Vec<char>>) -> Vec<(usize, usize)> {
-    let mut i = x;
-
-    // look left until we get the start of the number
-    loop {
-        if i == 0 {
-            break; // We found the start of the number
-        }
-
-        if (map[y][i - 1]).is_numeric() {
-            i -= 1;
-        }
-        else {
-            break;
-        }
-    }
-
-    let mut number: Vec<(usize, usize)> = Vec::new();
-    loop {
-        if i >= map[y].len() {
-            break; // We found the end of the number
-        }
-
-        if (map[y][i]).is_numeric() {
-            number.push((i, y));
-            i += 1;
-        }
-        else {
-            break;
-        }
-    }
+/// The original char parser, kept as a reference for the numbers and symbols `Schematic::parse` finds
+fn parse(input: String) -> (Vec<Number>, Vec<Symbol>) {
+    let map : Vec<Vec<char>> = input.lines()
+        .map(|line| line.chars().collect::<Vec<char>>())
+        .collect();
 
-    number
-}
+    let mut numbers = Vec::new();
+    let mut symbols = Vec::new();
+    for (y, line) in map.iter().enumerate() {
+        for (x, c) in line.iter().enumerate() {
+            if !c.is_ascii_digit() {
+                if *c != '.' {
+                    symbols.push(Symbol { symbol: *c, row: y, column: x });
+                }
+                continue;
+            }
 
-fn print_map(map: &Vec<Vec<char>>) {
-    for line in map {
-        for c in line {
-            print!("{}", c);
+            // Only start a number on its first digit
+            if x > 0 && line[x - 1].is_ascii_digit() {
+                continue;
+            }
+            let end = line[x..].iter().position(|c| !c.is_ascii_digit()).map_or(line.len(), |length| x + length);
+            let value = line[x..end].iter().collect::<String>().parse::<u64>().expect("Should be a number");
+            numbers.push(Number { value, row: y, start: x, end });
         }
-        println!("");
     }
+    (numbers, symbols)
 }
 
 #[cfg(test)]
@@ -136,27 +86,37 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_calculate_gear_ratio() {
+    fn test_gear_ratio() {
         let map_str = "\
         .12.3.\n\
         ...234\n\
         12*...";
 
-        let map = parse(map_str.to_string());
+        let schematic = Schematic::parse(map_str.as_bytes()).expect("Test - parse");
 
-        assert_eq!(calculate_gear_ratio(2, 2, &map), 12 * 234)
+        assert_eq!(schematic.gear_ratio_sum(), Ok(12 * 234))
     }
 
     #[test]
-    fn test_calculate_gear_ratio_two() {
+    fn test_gear_ratio_two() {
         let map_str = "\
         .12.3.\n\
         ...12.\n\
         12*...";
 
-        let map = parse(map_str.to_string());
+        let schematic = Schematic::parse(map_str.as_bytes()).expect("Test - parse");
+
+        assert_eq!(schematic.gear_ratio_sum(), Ok(144))
+    }
+
+    #[test]
+    fn test_parse_matches_schematic_parse() {
+        let map_str = ".12*3.\n...234\n12*...\n";
 
-        assert_eq!(calculate_gear_ratio(2, 2, &map), 144)
+        let schematic = Schematic::parse(map_str.as_bytes()).expect("Test - parse");
+        let (numbers, symbols) = parse(map_str.to_string());
+        assert_eq!(numbers[3], Number { value: 12, row: 2, start: 0, end: 2 });
+        assert_eq!((schematic.numbers, schematic.symbols), (numbers, symbols));
     }
 
     #[test]
@@ -166,8 +126,9 @@ mod tests {
         ...234\n\
         12*...";
 
-        let map = parse(map_str.to_string());
+        let schematic = Schematic::parse(map_str.as_bytes()).expect("Test - parse");
 
-        assert_eq!(calculate_answer(map), 12 * 234)
+        // The top gear has 12, 3 and 234 around it, so only the bottom one counts
+        assert_eq!(schematic.gear_ratio_sum(), Ok(12 * 234))
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,246 @@
+//! Counts the scratchcards you end up with without handing out every copy one at a time.
+//!
+//! Every copy of card `i` wins one copy of each of the next `matches[i]` cards, so once all the copies of card `i`
+//! are known, they can be added to those cards in one go. Going through the cards in order that takes
+//! O(cards × matches) additions, however many cards are won. The total grows exponentially with long chains of
+//! winning cards, so the counts can be kept in a `u64`, a `u128` or a [`BigCount`] of any size.
+//!
+//! Which cards are won and how many copies of each follows a [`CopyRule`], the puzzle's by default.
+
+use std::fmt;
+
+use crate::rules::CopyRule;
+
+/// A number of cards. Only addition is needed.
+pub trait Count: Clone + fmt::Display {
+    fn zero() -> Self;
+
+    fn one() -> Self;
+
+    /// Adds `other`, returning `None` if the sum doesn't fit
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+}
+
+impl Count for u64 {
+    fn zero() -> u64 {
+        0
+    }
+
+    fn one() -> u64 {
+        1
+    }
+
+    fn checked_add(&self, other: &u64) -> Option<u64> {
+        u64::checked_add(*self, *other)
+    }
+}
+
+impl Count for u128 {
+    fn zero() -> u128 {
+        0
+    }
+
+    fn one() -> u128 {
+        1
+    }
+
+    fn checked_add(&self, other: &u128) -> Option<u128> {
+        u128::checked_add(*self, *other)
+    }
+}
+
+/// An unsigned integer of any size, as base 2^64 limbs with the least significant first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigCount {
+    limbs: Vec<u64>,
+}
+
+impl Count for BigCount {
+    fn zero() -> BigCount {
+        BigCount { limbs: Vec::new() }
+    }
+
+    fn one() -> BigCount {
+        BigCount { limbs: vec![1] }
+    }
+
+    fn checked_add(&self, other: &BigCount) -> Option<BigCount> {
+        let mut limbs = Vec::with_capacity(self.limbs.len().max(other.limbs.len()) + 1);
+        let mut carry = false;
+        for i in 0..self.limbs.len().max(other.limbs.len()) {
+            let a = self.limbs.get(i).copied().unwrap_or(0);
+            let b = other.limbs.get(i).copied().unwrap_or(0);
+            let (sum, overflow_a) = a.overflowing_add(b);
+            let (sum, overflow_b) = sum.overflowing_add(carry as u64);
+            limbs.push(sum);
+            carry = overflow_a || overflow_b;
+        }
+        if carry {
+            limbs.push(1);
+        }
+        Some(BigCount { limbs })
+    }
+}
+
+impl fmt::Display for BigCount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Peel off 19 decimal digits at a time, the most that fit in a u64
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+
+        let mut limbs = self.limbs.clone();
+        let mut chunks = Vec::new();
+        while limbs.iter().any(|&limb| limb != 0) {
+            let mut remainder: u128 = 0;
+            for limb in limbs.iter_mut().rev() {
+                let value = (remainder << 64) | *limb as u128;
+                *limb = (value / CHUNK as u128) as u64;
+                remainder = value % CHUNK as u128;
+            }
+            chunks.push(remainder as u64);
+        }
+
+        match chunks.split_last() {
+            None => write!(f, "0"),
+            Some((most_significant, rest)) => {
+                write!(f, "{}", most_significant)?;
+                for chunk in rest.iter().rev() {
+                    write!(f, "{:019}", chunk)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+/// `count` added to itself `factor` times, by doubling
+fn scale<T: Count>(count: &T, factor: u64) -> Option<T> {
+    let mut total = T::zero();
+    let mut doubled = count.clone();
+    let mut factor = factor;
+    while factor > 0 {
+        if factor & 1 == 1 {
+            total = total.checked_add(&doubled)?;
+        }
+        factor >>= 1;
+        if factor > 0 {
+            doubled = doubled.checked_add(&doubled)?;
+        }
+    }
+    Some(total)
+}
+
+/// The number of copies of each card, given how many numbers each card matches and the rule for what they win.
+/// Returns `None` if a count doesn't fit in `T`.
+pub fn count_copies<T: Count>(matches: &[usize], rule: &CopyRule) -> Option<Vec<T>> {
+    let mut copies = vec![T::one(); matches.len()];
+    for (i, &count) in matches.iter().enumerate() {
+        // Copies added to card i by a wrapped window of its own are counted but don't win anything
+        let won = scale(&copies[i], rule.multiplier)?;
+        for target in rule.targets(i, count, matches.len()) {
+            copies[target] = copies[target].checked_add(&won)?;
+        }
+    }
+    Some(copies)
+}
+
+/// The total number of cards, or `None` if it doesn't fit in `T`
+pub fn count_cards<T: Count>(matches: &[usize], rule: &CopyRule) -> Option<T> {
+    count_copies::<T>(matches, rule)?.iter().try_fold(T::zero(), |total, count| total.checked_add(count))
+}
+
+/// The total number of cards in the smallest of `u64`, `u128` or [`BigCount`] that it fits in
+pub fn count_cards_any_size(matches: &[usize], rule: &CopyRule) -> String {
+    count_cards::<u64>(matches, rule).map(|count| count.to_string())
+        .or_else(|| count_cards::<u128>(matches, rule).map(|count| count.to_string()))
+        .unwrap_or_else(|| count_cards::<BigCount>(matches, rule).expect("Should always fit in a BigCount").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc::testing::{check_generated, Rng};
+    use crate::rules::Window;
+
+    /// Hands out every copy won one at a time, as a reference for any rule
+    fn count_copies_one_by_one(matches: &[usize], rule: &CopyRule) -> Vec<u64> {
+        let mut copies = vec![1; matches.len()];
+        for (i, &count) in matches.iter().enumerate() {
+            for _ in 0..copies[i] {
+                for target in rule.targets(i, count, matches.len()) {
+                    copies[target] += rule.multiplier;
+                }
+            }
+        }
+        copies
+    }
+
+    /// Every card matches enough numbers to win a copy of every card after it, so card `i` ends up with 2^i copies
+    fn doubling(cards: usize) -> Vec<usize> {
+        (0..cards).map(|i| cards - 1 - i).collect()
+    }
+
+    #[test]
+    fn example_copies() {
+        let matches = [4, 2, 2, 1, 0, 0];
+        assert_eq!(count_copies::<u64>(&matches, &CopyRule::default()), Some(vec![1, 2, 4, 8, 14, 1]));
+        assert_eq!(count_cards::<u64>(&matches, &CopyRule::default()), Some(30));
+        assert_eq!(count_cards_any_size(&matches, &CopyRule::default()), "30");
+    }
+
+    #[test]
+    fn wins_past_the_last_card_are_lost() {
+        assert_eq!(count_copies::<u64>(&[5, 5], &CopyRule::default()), Some(vec![1, 2]));
+        assert_eq!(count_cards_any_size(&[], &CopyRule::default()), "0");
+    }
+
+    #[test]
+    fn counts_move_to_bigger_types() {
+        assert_eq!(count_cards::<u64>(&doubling(64), &CopyRule::default()), Some(u64::MAX));
+        assert_eq!(count_cards::<u64>(&doubling(65), &CopyRule::default()), None);
+        assert_eq!(count_cards::<u128>(&doubling(65), &CopyRule::default()), Some((1 << 65) - 1));
+        assert_eq!(count_cards_any_size(&doubling(128), &CopyRule::default()), u128::MAX.to_string());
+        assert_eq!(count_cards::<u128>(&doubling(129), &CopyRule::default()), None);
+        assert_eq!(count_cards_any_size(&doubling(200), &CopyRule::default()), "1606938044258990275541962092341162602522202993782792835301375");
+    }
+
+    #[test]
+    fn example_with_other_rules() {
+        let matches = [4, 2, 2, 1, 0, 0];
+        let wrap = CopyRule { window: Window::Wrap, multiplier: 1 };
+        let triple = CopyRule { window: Window::Clamp, multiplier: 3 };
+        assert_eq!(count_copies::<u64>(&[1, 0, 3], &wrap), Some(vec![2, 3, 2]));
+        assert_eq!(count_copies::<u64>(&matches, &triple), Some(vec![1, 4, 16, 64, 244, 1]));
+        assert_eq!(count_cards::<u64>(&matches, &CopyRule { multiplier: 0, ..triple }), Some(6));
+    }
+
+    #[test]
+    fn rules_match_one_by_one_on_generated_tables() {
+        // Match counts, a few larger than the table to wrap more than once
+        let generate = |rng: &mut Rng, cards: usize| -> Vec<usize> {
+            (0..cards).map(|_| if rng.below(8) == 0 { cards + rng.below(3) as usize } else { rng.below(3) as usize }).collect()
+        };
+        check_generated(46, 12, generate, |matches| {
+            for window in [Window::Clamp, Window::Wrap] {
+                for multiplier in 0..3 {
+                    let rule = CopyRule { window, multiplier };
+                    assert_eq!(count_copies::<u64>(matches, &rule), Some(count_copies_one_by_one(matches, &rule)), "{:?}", rule);
+                    assert_eq!(count_cards::<BigCount>(matches, &rule).map(|count| count.to_string()),
+                        count_cards::<u64>(matches, &rule).map(|count| count.to_string()));
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn big_count_display() {
+        let mut count = BigCount::one();
+        assert_eq!(count.to_string(), "1");
+        for _ in 0..64 {
+            count = count.checked_add(&count).expect("Test - add");
+        }
+        assert_eq!(count.to_string(), "18446744073709551616");
+        assert_eq!(BigCount::zero().to_string(), "0");
+        // The lower chunk of digits keeps its leading zeros
+        assert_eq!(BigCount { limbs: vec![10_000_000_000_000_000_000] }.to_string(), "10000000000000000000");
+    }
+}
@@ -0,0 +1,262 @@
+//! Scratchcard model shared by both parts of day 4.
+//!
+//! A line such as `Card 3: 1 21 53 | 69 82 1 21` is parsed into a [`Scratchcard`] with its id, the winning numbers
+//! left of the bar and the numbers you have right of it. The parser rejects a number that appears twice on one side
+//! and cards that aren't numbered 1, 2, 3 and so on, since part 2 wins copies of cards by their position.
+
+pub mod copies;
+pub mod rules;
+
+use std::{fmt::{self, Write}, str::FromStr};
+
+use aoc::input;
+use copies::{BigCount, Count};
+use rules::{Rules, Scoring};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scratchcard {
+    pub id: u32,
+    pub winning: Vec<u32>,
+    pub have: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Winning,
+    Have,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseCardError {
+    MissingHeader,
+    InvalidId(String),
+    MissingBar,
+    InvalidNumber(String),
+    DuplicateNumber(Side, u32),
+    OutOfOrder { expected: u32, found: u32 },
+}
+
+impl fmt::Display for ParseCardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseCardError::MissingHeader => write!(f, "expected the line to start with `Card <id>:`"),
+            ParseCardError::InvalidId(id) => write!(f, "`{}` is not a card id", id),
+            ParseCardError::MissingBar => write!(f, "expected `|` between the winning numbers and yours"),
+            ParseCardError::InvalidNumber(number) => write!(f, "`{}` is not a number", number),
+            ParseCardError::DuplicateNumber(Side::Winning, number) => write!(f, "{} is a winning number twice", number),
+            ParseCardError::DuplicateNumber(Side::Have, number) => write!(f, "you have {} twice", number),
+            ParseCardError::OutOfOrder { expected, found } => write!(f, "expected card {}, found card {}", expected, found),
+        }
+    }
+}
+
+/// A parse error and the 1-based line it happened on
+#[derive(Debug, PartialEq)]
+pub struct LineError {
+    pub line: usize,
+    pub error: ParseCardError,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.error)
+    }
+}
+
+impl Scratchcard {
+    /// Parses one card on its own, without checking its id against the cards before it
+    pub fn parse_bytes(line: &[u8]) -> Result<Scratchcard, ParseCardError> {
+        let lossy = |bytes: &[u8]| String::from_utf8_lossy(bytes).to_string();
+
+        let rest = line.strip_prefix(b"Card").ok_or(ParseCardError::MissingHeader)?;
+        let (id, numbers) = input::split_once(rest, b':').ok_or(ParseCardError::MissingHeader)?;
+        let id = input::parse_u64(id.trim_ascii())
+            .and_then(|id| u32::try_from(id).ok())
+            .ok_or_else(|| ParseCardError::InvalidId(lossy(id.trim_ascii())))?;
+
+        let (winning, have) = input::split_once(numbers, b'|').ok_or(ParseCardError::MissingBar)?;
+        Ok(Scratchcard { id, winning: parse_side(winning, Side::Winning)?, have: parse_side(have, Side::Have)? })
+    }
+
+    /// The numbers you have that are winning numbers, in the order you have them
+    pub fn matching_numbers(&self) -> impl Iterator<Item = u32> + '_ {
+        self.have.iter().copied().filter(|number| self.winning.contains(number))
+    }
+
+    pub fn matches(&self) -> usize {
+        self.matching_numbers().count()
+    }
+
+    /// The points under `scoring`, `None` if they don't fit in a u64
+    pub fn score(&self, scoring: Scoring) -> Option<u64> {
+        scoring.points(self.matches())
+    }
+}
+
+fn parse_side(bytes: &[u8], side: Side) -> Result<Vec<u32>, ParseCardError> {
+    let mut numbers = Vec::new();
+    for number in bytes.split(|b| b.is_ascii_whitespace()).filter(|number| !number.is_empty()) {
+        let number = input::parse_u64(number)
+            .and_then(|number| u32::try_from(number).ok())
+            .ok_or_else(|| ParseCardError::InvalidNumber(String::from_utf8_lossy(number).to_string()))?;
+        if numbers.contains(&number) {
+            return Err(ParseCardError::DuplicateNumber(side, number));
+        }
+        numbers.push(number);
+    }
+    Ok(numbers)
+}
+
+impl FromStr for Scratchcard {
+    type Err = ParseCardError;
+
+    fn from_str(line: &str) -> Result<Scratchcard, ParseCardError> {
+        Scratchcard::parse_bytes(line.as_bytes())
+    }
+}
+
+impl fmt::Display for Scratchcard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let join = |numbers: &[u32]| numbers.iter().map(|number| number.to_string()).collect::<Vec<String>>().join(" ");
+        write!(f, "Card {}: {} | {}", self.id, join(&self.winning), join(&self.have))
+    }
+}
+
+/// Checks that the card on every line has the id after the one before it, starting from 1
+pub struct IdCheck {
+    expected: u32,
+}
+
+impl IdCheck {
+    pub fn new() -> IdCheck {
+        IdCheck { expected: 1 }
+    }
+
+    pub fn check(&mut self, card: &Scratchcard) -> Result<(), ParseCardError> {
+        if card.id != self.expected {
+            return Err(ParseCardError::OutOfOrder { expected: self.expected, found: card.id });
+        }
+        self.expected += 1;
+        Ok(())
+    }
+}
+
+impl Default for IdCheck {
+    fn default() -> IdCheck {
+        IdCheck::new()
+    }
+}
+
+/// Parses every line of the input into a card, checking that they're numbered in order
+pub fn parse_cards(contents: &[u8]) -> Result<Vec<Scratchcard>, LineError> {
+    let mut ids = IdCheck::new();
+    input::lines(contents)
+        .enumerate()
+        .map(|(i, line)| {
+            Scratchcard::parse_bytes(line)
+                .and_then(|card| ids.check(&card).map(|_| card))
+                .map_err(|error| LineError { line: i + 1, error })
+        })
+        .collect()
+}
+
+/// How many numbers each card matches
+pub fn matches(cards: &[Scratchcard]) -> Vec<usize> {
+    cards.iter().map(|card| card.matches()).collect()
+}
+
+/// Part 1: the sum of every card's points, `None` if it doesn't fit in a u64
+pub fn points_sum(cards: &[Scratchcard], scoring: Scoring) -> Option<u64> {
+    cards.iter().try_fold(0u64, |sum, card| sum.checked_add(card.score(scoring)?))
+}
+
+/// A row per card with its matches, points and the copies of it you end up with under `rules`
+pub fn render_table(cards: &[Scratchcard], rules: &Rules) -> String {
+    let copies = copies::count_copies::<BigCount>(&matches(cards), &rules.copies).expect("Should always fit in a BigCount");
+    let total = copies.iter().try_fold(BigCount::zero(), |total, count| total.checked_add(count))
+        .expect("Should always fit in a BigCount");
+
+    let mut table = String::new();
+    writeln!(table, "{:>6} {:>8} {:>12} {:>12}", "card", "matches", "points", "copies").unwrap();
+    for (card, copies) in cards.iter().zip(&copies) {
+        let points = card.score(rules.scoring).map_or(String::from("overflow"), |points| points.to_string());
+        writeln!(table, "{:>6} {:>8} {:>12} {:>12}", card.id, card.matches(), points, copies.to_string()).unwrap();
+    }
+    let points = points_sum(cards, rules.scoring).map_or(String::from("overflow"), |points| points.to_string());
+    writeln!(table, "{:>6} {:>8} {:>12} {:>12}", "total", "", points, total.to_string()).unwrap();
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &[u8] = b"\
+Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
+";
+
+    #[test]
+    fn parse_keeps_ids_and_sides() {
+        let card: Scratchcard = "Card  12: 41 48 | 83 41  6".parse().expect("Test - parse");
+        assert_eq!(card, Scratchcard { id: 12, winning: vec![41, 48], have: vec![83, 41, 6] });
+        assert_eq!(card.to_string(), "Card 12: 41 48 | 83 41 6");
+        assert_eq!(card.to_string().parse::<Scratchcard>(), Ok(card));
+    }
+
+    #[test]
+    fn example_answers() {
+        let cards = parse_cards(EXAMPLE).expect("Test - parse");
+        assert_eq!(matches(&cards), vec![4, 2, 2, 1, 0, 0]);
+        assert_eq!(cards[0].matching_numbers().collect::<Vec<u32>>(), vec![83, 86, 17, 48]);
+        assert_eq!(points_sum(&cards, Scoring::Doubling), Some(13));
+        assert_eq!(copies::count_cards::<u64>(&matches(&cards), &rules::CopyRule::default()), Some(30));
+    }
+
+    #[test]
+    fn parse_reports_bad_cards() {
+        let parse = |line: &str| line.parse::<Scratchcard>();
+        assert_eq!(parse("1: 2 | 3"), Err(ParseCardError::MissingHeader));
+        assert_eq!(parse("Card x: 2 | 3"), Err(ParseCardError::InvalidId(String::from("x"))));
+        assert_eq!(parse("Card 1: 2 3"), Err(ParseCardError::MissingBar));
+        assert_eq!(parse("Card 1: 2 -3 | 3"), Err(ParseCardError::InvalidNumber(String::from("-3"))));
+        assert_eq!(parse("Card 1: 2 5 2 | 3"), Err(ParseCardError::DuplicateNumber(Side::Winning, 2)));
+        assert_eq!(parse("Card 1: 2 | 3 3"), Err(ParseCardError::DuplicateNumber(Side::Have, 3)));
+        assert_eq!(parse("Card 1: | "), Ok(Scratchcard { id: 1, winning: Vec::new(), have: Vec::new() }));
+    }
+
+    #[test]
+    fn parse_cards_checks_the_order() {
+        assert_eq!(parse_cards(b"Card 1: 1 | 1\nCard 3: 1 | 1\n"), Err(LineError {
+            line: 2,
+            error: ParseCardError::OutOfOrder { expected: 2, found: 3 },
+        }));
+        assert_eq!(parse_cards(b"Card 2: 1 | 1\n").map_err(|error| error.to_string()),
+            Err(String::from("Line 1: expected card 1, found card 2")));
+    }
+
+    #[test]
+    fn table_has_a_row_per_card() {
+        let cards = parse_cards(EXAMPLE).expect("Test - parse");
+        let table = render_table(&cards, &Rules::default());
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 8);
+        assert_eq!(lines[1], "     1        4            8            1");
+        assert_eq!(lines[5], "     5        0            0           14");
+        assert_eq!(lines[7], " total                    13           30");
+    }
+
+    #[test]
+    fn table_follows_the_rules() {
+        let cards = parse_cards(EXAMPLE).expect("Test - parse");
+        let rules = Rules { scoring: Scoring::Fibonacci, copies: rules::CopyRule { window: rules::Window::Clamp, multiplier: 2 } };
+        let table = render_table(&cards, &rules);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[1], "     1        4            5            1");
+        assert_eq!(lines[7], " total                    10          116");
+    }
+}
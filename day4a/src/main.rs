@@ -1,39 +1,131 @@
-use std::{fs, collections::HashSet};
+use std::{env, fs};
+
+use aoc::{breakdown::{self, Breakdown, Value}, input::{self, StreamArgs}};
+use day4::{rules::{self, Scoring}, IdCheck, LineError, Scratchcard};
 
 fn main() {
-    
+    let args: Vec<String> = env::args().collect();
+    let rules = aoc::unwrap_or_exit(rules::rules_from_args(&args));
+    if !rules.is_standard() {
+        println!("Rules: {}", rules);
+    }
+
+    aoc::unwrap_or_exit(breakdown::check_not_streaming(&args));
+    if let Some(stream) = aoc::unwrap_or_exit(StreamArgs::from_args(&args, "input.txt")) {
+        let mut ids = IdCheck::new();
+        let mut line = 0;
+        let sum = stream.try_fold_lines(0u64, |sum, card| {
+            line += 1;
+            let card = Scratchcard::parse_bytes(card).and_then(|card| ids.check(&card).map(|_| card));
+            let card = aoc::unwrap_or_exit(card.map_err(|error| LineError { line, error }));
+            let points = aoc::unwrap_or_exit(card.score(rules.scoring).ok_or(format!("Line {}: too many points for a u64", line)));
+            sum.checked_add(points).ok_or("The sum doesn't fit in a u64")
+        })
+            .expect("Should have been able to read the input");
+        println!("Sum: {}", aoc::unwrap_or_exit(sum));
+        return;
+    }
+
     let input = fs::read_to_string("input.txt")
         .expect("Should be able to read the file input.txt");
 
-    let mut sum = 0;
+    if args.iter().any(|arg| arg == "--compare-parsers") {
+        input::compare_parsers(&input, 100, parse_cards, |input| aoc::unwrap_or_exit(day4::parse_cards(input)));
+        return;
+    }
+
+    let cards = aoc::unwrap_or_exit(day4::parse_cards(input.as_bytes()));
+
+    if let Some(path) = breakdown::path_from_args(&args) {
+        get_breakdown(input.as_bytes(), &cards, rules.scoring).write(path)
+            .expect("Should have been able to write the breakdown");
+        println!("Breakdown written to {}", path);
+    }
+
+    if args.iter().any(|arg| arg == "--table") {
+        print!("{}", day4::render_table(&cards, &rules));
+    }
+
+    let sum = day4::points_sum(&cards, rules.scoring).ok_or("The sum doesn't fit in a u64");
+    println!("Sum: {}", aoc::unwrap_or_exit(sum));
+}
+
+fn get_breakdown(input: &[u8], cards: &[Scratchcard], scoring: Scoring) -> Breakdown {
+    let mut breakdown = Breakdown::new(&["matching_numbers"]);
+    for ((i, line), card) in input::lines(input).enumerate().zip(cards) {
+        let points = card.score(scoring).map_or(Value::text("overflow"), Value::number);
+        breakdown.add_value(i + 1, line, points, vec![Value::number(card.matches())]);
+    }
+    breakdown
+}
+
+/// The original parser, kept as a reference for `day4::parse_cards`. It doesn't check the cards, so it only agrees
+/// with the library on valid input.
+fn parse_cards(input: &str) -> Vec<Scratchcard> {
+    let mut cards = Vec::new();
     for line in input.lines() {
         let mut game_info = line.split(':');
-        game_info.next();
+        let id = game_info.next()
+            .expect("Should be the card id")
+            .trim_start_matches("Card")
+            .trim()
+            .parse::<u32>()
+            .expect("Should be a number");
         let game = game_info.next()
             .expect("Should be the game");
 
         let mut game = game.split('|');
-        let your_numbers: HashSet<&str> = HashSet::from_iter(game.next()
-            .expect("Should be your numbers")
+        let winning: Vec<u32> = game.next()
+            .expect("Should be the winning numbers")
             .split_ascii_whitespace()
-            .filter(|&x| !x.is_empty()));
+            .map(|x| x.parse::<u32>().expect("Should be a number"))
+            .collect();
 
-        let winning_numbers: HashSet<&str> = HashSet::from_iter(game.next()
-            .expect("Should be the winning numbers")
+        let have: Vec<u32> = game.next()
+            .expect("Should be your numbers")
             .split_ascii_whitespace()
-            .filter(|&x| !x.is_empty()));
+            .map(|x| x.parse::<u32>().expect("Should be a number"))
+            .collect();
 
-        let matching_numbers = your_numbers.intersection(&winning_numbers);
+        cards.push(Scratchcard { id, winning, have });
+    }
+    cards
+}
 
-        let matching_numbers = matching_numbers.count();
-        if matching_numbers == 0 {
-            sum += 0;
-        }
-        else {
-            let base: u32 = 2;
-            sum += base.pow(matching_numbers as u32 - 1);
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn library_parser_matches_parse_cards() {
+        let input = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53\nCard 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19\n";
+        let cards = day4::parse_cards(input.as_bytes()).expect("Test - parse");
+
+        assert_eq!(cards, parse_cards(input));
+        assert_eq!(cards[0].winning, vec![41, 48, 83, 86, 17]);
+    }
+
+    #[test]
+    fn breakdown_has_a_row_per_card() {
+        let input = b"Card 1: 41 48 | 48 41 6\nCard 2: 13 | 61\n";
+        let cards = day4::parse_cards(input).expect("Test - parse");
+        assert_eq!(get_breakdown(input, &cards, Scoring::Doubling).to_csv(),
+            "line,raw,contribution,matching_numbers\n1,Card 1: 41 48 | 48 41 6,2,2\n2,Card 2: 13 | 61,0,0\n");
     }
 
-    println!("Sum: {}", sum);
+    #[test]
+    fn breakdown_json_quotes_overflowing_cards() {
+        // 65 matches are worth 2^64 points
+        let numbers: Vec<String> = (1..=65).map(|number| number.to_string()).collect();
+        let line = format!("Card 1: {} | {}", numbers.join(" "), numbers.join(" "));
+        let cards = day4::parse_cards(line.as_bytes()).expect("Test - parse");
+
+        let path = std::env::temp_dir().join("day4a_breakdown_overflow.json");
+        let path = path.to_str().expect("Test - path");
+        get_breakdown(line.as_bytes(), &cards, Scoring::Doubling).write(path).expect("Test - write");
+        let json = fs::read_to_string(path).expect("Test - read");
+        fs::remove_file(path).expect("Test - remove");
+
+        assert_eq!(json, format!("[\n  {{\"line\": 1, \"raw\": \"{}\", \"contribution\": \"overflow\", \"matching_numbers\": 65}}\n]\n", line));
+    }
 }
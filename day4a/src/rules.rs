@@ -0,0 +1,190 @@
+//! Rules for variants of the scratchcard game, chosen at runtime.
+//!
+//! The puzzle scores a card 2^(matches - 1) points and has each copy of a card win one copy of each of the next
+//! `matches` cards, losing wins past the end of the table. [`Scoring`] swaps the points for linear or Fibonacci
+//! ones, and [`CopyRule`] lets wins wrap around to the top of the table and hand out more than one copy each.
+
+use std::fmt;
+
+/// How many points a card with some matches is worth
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Scoring {
+    /// 1, 2, 4, 8, ...: the puzzle's scoring
+    #[default]
+    Doubling,
+    /// 1, 2, 3, 4, ...: a point per match
+    Linear,
+    /// 1, 2, 3, 5, 8, ...: each match after the second is worth the points of the two before it added up
+    Fibonacci,
+}
+
+impl Scoring {
+    pub fn parse(text: &str) -> Result<Scoring, String> {
+        match text {
+            "doubling" => Ok(Scoring::Doubling),
+            "linear" => Ok(Scoring::Linear),
+            "fibonacci" => Ok(Scoring::Fibonacci),
+            text => Err(format!("Unknown scoring `{}`, expected doubling, linear or fibonacci", text)),
+        }
+    }
+
+    /// The points for a card, `None` if they don't fit in a u64. No matches are worth nothing.
+    pub fn points(self, matches: usize) -> Option<u64> {
+        if matches == 0 {
+            return Some(0);
+        }
+        match self {
+            Scoring::Doubling => 1u64.checked_shl(u32::try_from(matches - 1).ok()?),
+            Scoring::Linear => u64::try_from(matches).ok(),
+            Scoring::Fibonacci => {
+                let (mut previous, mut points): (u64, u64) = (1, 1);
+                for _ in 1..matches {
+                    (previous, points) = (points, previous.checked_add(points)?);
+                }
+                Some(points)
+            },
+        }
+    }
+}
+
+/// What happens to wins that run past the last card
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Window {
+    /// They're lost: the puzzle's rule
+    #[default]
+    Clamp,
+    /// They carry on from the first card. The table is scratched once from top to bottom, so copies won of cards
+    /// that have already been scratched are counted but don't win anything themselves.
+    Wrap,
+}
+
+impl Window {
+    pub fn parse(text: &str) -> Result<Window, String> {
+        match text {
+            "clamp" => Ok(Window::Clamp),
+            "wrap" => Ok(Window::Wrap),
+            text => Err(format!("Unknown window `{}`, expected clamp or wrap", text)),
+        }
+    }
+}
+
+/// Which copies a card wins: `multiplier` copies of each of the next `matches` cards
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CopyRule {
+    pub window: Window,
+    pub multiplier: u64,
+}
+
+impl CopyRule {
+    /// The cards that card `card` wins copies of, in a table of `cards` cards with `matches` matches. With
+    /// [`Window::Wrap`] a card can come up more than once if `matches` is larger than the table.
+    pub fn targets(&self, card: usize, matches: usize, cards: usize) -> impl Iterator<Item = usize> {
+        let window = self.window;
+        (card + 1..card + 1 + matches).map_while(move |target| match window {
+            Window::Clamp => Some(target).filter(|&target| target < cards),
+            Window::Wrap => Some(target % cards),
+        })
+    }
+}
+
+impl Default for CopyRule {
+    fn default() -> CopyRule {
+        CopyRule { window: Window::Clamp, multiplier: 1 }
+    }
+}
+
+/// The scoring for part 1 and the copy rule for part 2
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Rules {
+    pub scoring: Scoring,
+    pub copies: CopyRule,
+}
+
+impl Rules {
+    pub fn is_standard(&self) -> bool {
+        *self == Rules::default()
+    }
+}
+
+impl fmt::Display for Rules {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let scoring = match self.scoring {
+            Scoring::Doubling => "doubling",
+            Scoring::Linear => "linear",
+            Scoring::Fibonacci => "fibonacci",
+        };
+        let window = match self.copies.window {
+            Window::Clamp => "lost",
+            Window::Wrap => "wrapped",
+        };
+        write!(f, "{} points, {} {} of each card won, wins past the end {}", scoring, self.copies.multiplier,
+            if self.copies.multiplier == 1 { "copy" } else { "copies" }, window)
+    }
+}
+
+/// Reads the rules from `--scoring <doubling|linear|fibonacci>`, `--window <clamp|wrap>` and `--multiplier <copies>`.
+/// Each one that isn't given is the puzzle's.
+pub fn rules_from_args(args: &[String]) -> Result<Rules, String> {
+    let get_option = |name: &str| args.iter()
+        .position(|arg| arg == name)
+        .map(|position| args.get(position + 1).map(|value| value.as_str()).ok_or(format!("{} needs a value", name)));
+
+    let mut rules = Rules::default();
+    if let Some(scoring) = get_option("--scoring") {
+        rules.scoring = Scoring::parse(scoring?)?;
+    }
+    if let Some(window) = get_option("--window") {
+        rules.copies.window = Window::parse(window?)?;
+    }
+    if let Some(multiplier) = get_option("--multiplier") {
+        let multiplier = multiplier?;
+        rules.copies.multiplier = multiplier.parse()
+            .map_err(|_| format!("--multiplier needs a number of copies, not `{}`", multiplier))?;
+    }
+    Ok(rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scorings() {
+        let points = |scoring: Scoring| (0..7).map(|matches| scoring.points(matches).expect("Test - points")).collect::<Vec<u64>>();
+        assert_eq!(points(Scoring::Doubling), vec![0, 1, 2, 4, 8, 16, 32]);
+        assert_eq!(points(Scoring::Linear), vec![0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(points(Scoring::Fibonacci), vec![0, 1, 2, 3, 5, 8, 13]);
+    }
+
+    #[test]
+    fn points_that_dont_fit_are_none() {
+        assert_eq!(Scoring::Doubling.points(64), Some(1 << 63));
+        assert_eq!(Scoring::Doubling.points(65), None);
+        assert_eq!(Scoring::Fibonacci.points(92), Some(12200160415121876738));
+        assert_eq!(Scoring::Fibonacci.points(93), None);
+    }
+
+    #[test]
+    fn targets_clamp_or_wrap() {
+        let targets = |window: Window, card: usize, matches: usize| {
+            CopyRule { window, multiplier: 1 }.targets(card, matches, 4).collect::<Vec<usize>>()
+        };
+        assert_eq!(targets(Window::Clamp, 1, 2), vec![2, 3]);
+        assert_eq!(targets(Window::Clamp, 2, 3), vec![3]);
+        assert_eq!(targets(Window::Wrap, 2, 3), vec![3, 0, 1]);
+        assert_eq!(targets(Window::Wrap, 3, 5), vec![0, 1, 2, 3, 0]);
+    }
+
+    #[test]
+    fn rules_from_args_works() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<String>>();
+        assert_eq!(rules_from_args(&args(&["day4a"])), Ok(Rules::default()));
+        assert_eq!(rules_from_args(&args(&["day4b", "--window", "wrap", "--multiplier", "3", "--scoring", "linear"])), Ok(Rules {
+            scoring: Scoring::Linear,
+            copies: CopyRule { window: Window::Wrap, multiplier: 3 },
+        }));
+        assert_eq!(rules_from_args(&args(&["day4b", "--multiplier", "x"])), Err(String::from("--multiplier needs a number of copies, not `x`")));
+        assert_eq!(rules_from_args(&args(&["day4b", "--window"])), Err(String::from("--window needs a value")));
+        assert_eq!(Rules::default().to_string(), "doubling points, 1 copy of each card won, wins past the end lost");
+    }
+}
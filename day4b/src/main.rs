@@ -1,4 +1,7 @@
-use std::{fs, collections::{HashSet, VecDeque}};
+use std::{env, fs, process, collections::{HashSet, VecDeque}};
+
+use aoc::input;
+use day4::{copies, rules::{self, CopyRule}};
 
 struct Queue<T> {
     queue: VecDeque<T>,
@@ -23,13 +26,46 @@ impl<T> Queue<T> {
 }
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
+    let rules = aoc::unwrap_or_exit(rules::rules_from_args(&args));
+    if !rules.is_standard() {
+        println!("Rules: {}", rules);
+    }
+
     let contents = fs::read_to_string("input.txt")
         .expect("Should have been able to read file input.txt");
 
-    let cards:Vec<HashSet<u32>> = parse_input(&contents);
+    if args.iter().any(|arg| arg == "--compare-parsers") {
+        input::compare_parsers(&contents, 100, parse_input, matching_numbers);
+        return;
+    }
 
+    let cards = aoc::unwrap_or_exit(day4::parse_cards(contents.as_bytes()));
+    let matches = day4::matches(&cards);
+
+    if args.iter().any(|arg| arg == "--compare-solvers") {
+        if rules.copies != CopyRule::default() {
+            eprintln!("--compare-solvers only supports the puzzle's copy rule");
+            process::exit(1);
+        }
+        input::compare_implementations(&matches[..], 10,
+            ("queue", |matches| count_cards_queue(matches).to_string()),
+            ("copies", |matches| copies::count_cards_any_size(matches, &CopyRule::default())));
+        return;
+    }
+
+    if args.iter().any(|arg| arg == "--table") {
+        print!("{}", day4::render_table(&cards, &rules));
+    }
+
+    println!("Number of cards: {}", copies::count_cards_any_size(&matches, &rules.copies));
+}
+
+/// The original solver, kept as a reference for `copies::count_cards`: every card won goes through a queue, so the
+/// work grows with the number of cards won rather than the number of cards in the table
+fn count_cards_queue(matches: &[usize]) -> u64 {
     let mut queue: Queue<usize> = Queue::new();
-    for (i, _) in cards.iter().enumerate() {
+    for (i, _) in matches.iter().enumerate() {
         queue.enqueue(i);
     }
 
@@ -37,34 +73,34 @@ fn main() {
     while queue.length() > 0 {
         let el = queue.dequeue().expect("Should have been able to dequeue");
 
-        if el >= cards.len() {
+        if el >= matches.len() {
             continue;
         }
 
         number_of_cards += 1;
 
-        let card = cards.get(el).expect("Should have been able to get card");
-        let number_matches = card.len();
+        let number_matches = *matches.get(el).expect("Should have been able to get card");
 
         for i in 0..number_matches {
             queue.enqueue(el + i + 1);
         }
     }
 
-    println!("Number of cards: {}", number_of_cards);
+    number_of_cards
 }
 
+/// The original parser, kept as a reference for `day4::parse_cards`: the numbers each card matches
 fn parse_input(contents: &str) -> Vec<HashSet<u32>>
 {
     let mut cards: Vec::<HashSet<u32>>  = Vec::new();
-    
+
     for line in contents.lines() {
         let mut iter = line.split(":");
         iter.next(); // Skip card number
         let card = iter.next()
             .expect("Should have card");
         let mut card_iter = card.split("|");
-        
+
         let left_side = parse_card_numbers(card_iter.next().expect("Should have left side"));
         let right_side = parse_card_numbers(card_iter.next().expect("Should have right side"));
 
@@ -74,10 +110,56 @@ fn parse_input(contents: &str) -> Vec<HashSet<u32>>
     cards
 }
 
+/// Same as `parse_input`, through the library's validating parser
+fn matching_numbers(contents: &[u8]) -> Vec<HashSet<u32>> {
+    aoc::unwrap_or_exit(day4::parse_cards(contents)).iter()
+        .map(|card| card.matching_numbers().collect())
+        .collect()
+}
+
 fn parse_card_numbers(card: &str) -> HashSet<u32>
 {
     let ret: HashSet<u32>  = HashSet::from_iter(card.split(' ')
         .filter(|&s| !s.is_empty())
         .map(|s| s.parse::<u32>().expect("Should have been able to parse as number")));
     ret
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc::testing::check_generated;
+
+    #[test]
+    fn matching_numbers_match_parse_input() {
+        let contents = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53\nCard 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19\n";
+        let cards = matching_numbers(contents.as_bytes());
+
+        assert_eq!(cards, parse_input(contents));
+        assert_eq!(cards[0], HashSet::from([48, 83, 86, 17]));
+    }
+
+    #[test]
+    fn copies_match_the_queue() {
+        let contents = "\
+Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
+";
+        let cards = day4::parse_cards(contents.as_bytes()).expect("Test - parse");
+        let matches = day4::matches(&cards);
+        assert_eq!(count_cards_queue(&matches), 30);
+        assert_eq!(copies::count_cards::<u64>(&matches, &CopyRule::default()), Some(30));
+    }
+
+    #[test]
+    fn copies_match_the_queue_on_generated_tables() {
+        // Match counts kept small enough for the queue to finish
+        check_generated(4, 40, |rng, cards| (0..cards).map(|_| rng.below(4) as usize).collect::<Vec<usize>>(), |matches| {
+            assert_eq!(copies::count_cards::<u64>(matches, &CopyRule::default()), Some(count_cards_queue(matches)));
+        });
+    }
+}
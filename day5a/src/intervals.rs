@@ -0,0 +1,346 @@
+//! Pushes whole ranges of seeds through the almanac instead of one seed at a time.
+//!
+//! A range of values that lies inside one mapping range is shifted by the same amount, so it stays a range. Each
+//! stage splits the ranges it's given at the edges of its mapping ranges, shifts the pieces that are mapped and
+//! passes the rest through unchanged. The number of ranges only grows with the number of mapping ranges they cross,
+//! however many seeds they hold, and the lowest location is the lowest start of the ranges that come out at the end.
+//!
+//! Going the other way, each map splits every possible value into pieces that are each shifted by one offset, so
+//! the values that end up in an interval are that interval shifted back by each piece's offset, kept within the
+//! piece. That answers which seeds end up at some locations without trying every seed.
+
+use std::fmt;
+
+use crate::{CategoryMap, Mappings};
+
+/// The values from `start` to `end`, both included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Interval {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl Interval {
+    /// The interval of `length` values from `start`, `None` if it's empty or ends past the largest i64
+    pub fn from_length(start: i64, length: i64) -> Option<Interval> {
+        if length <= 0 {
+            return None;
+        }
+        Some(Interval { start, end: start.checked_add(length - 1)? })
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}", self.start)
+        } else {
+            write!(f, "{}-{}", self.start, self.end)
+        }
+    }
+}
+
+/// The intervals separated by commas, `none` if there aren't any
+pub fn describe(intervals: &[Interval]) -> String {
+    if intervals.is_empty() {
+        return String::from("none");
+    }
+    intervals.iter().map(|interval| interval.to_string()).collect::<Vec<String>>().join(", ")
+}
+
+/// Sorts the intervals and merges the ones that overlap or touch
+pub fn normalize(mut intervals: Vec<Interval>) -> Vec<Interval> {
+    intervals.sort();
+    let mut merged: Vec<Interval> = Vec::with_capacity(intervals.len());
+    for interval in intervals {
+        match merged.last_mut() {
+            Some(last) if interval.start <= last.end.saturating_add(1) => last.end = last.end.max(interval.end),
+            _ => merged.push(interval),
+        }
+    }
+    merged
+}
+
+impl Mappings {
+    /// Same as `convert_value` for every value in the intervals, returned sorted and merged. A value in more than
+    /// one mapping range goes through the first one, like in `convert_value`.
+    pub fn convert_intervals(&self, intervals: &[Interval]) -> Vec<Interval> {
+        let mut converted = Vec::new();
+        let mut pending = intervals.to_vec();
+        for range in &self.mappings {
+            let mut unmapped = Vec::new();
+            for interval in pending {
+                if interval.end < range.min || interval.start > range.max {
+                    unmapped.push(interval);
+                    continue;
+                }
+
+                let overlap = Interval { start: interval.start.max(range.min), end: interval.end.min(range.max) };
+                converted.push(Interval { start: overlap.start - range.conversion, end: overlap.end - range.conversion });
+                if interval.start < overlap.start {
+                    unmapped.push(Interval { start: interval.start, end: overlap.start - 1 });
+                }
+                if interval.end > overlap.end {
+                    unmapped.push(Interval { start: overlap.end + 1, end: interval.end });
+                }
+            }
+            pending = unmapped;
+        }
+
+        // Values outside every mapping range keep their number
+        converted.extend(pending);
+        normalize(converted)
+    }
+
+    /// Every possible value split into intervals, sorted, each with the offset `convert_value` adds to its values.
+    /// Values outside every mapping range are in pieces with an offset of 0. An offset can take a value from one end
+    /// of an i64 to the other, so it's an i128.
+    pub fn pieces(&self) -> Vec<(Interval, i128)> {
+        let mut pieces = Vec::new();
+        let mut pending = vec![Interval { start: i64::MIN, end: i64::MAX }];
+        for range in &self.mappings {
+            let mut unmapped = Vec::new();
+            for interval in pending {
+                if interval.end < range.min || interval.start > range.max {
+                    unmapped.push(interval);
+                    continue;
+                }
+
+                let overlap = Interval { start: interval.start.max(range.min), end: interval.end.min(range.max) };
+                pieces.push((overlap, -(range.conversion as i128)));
+                if interval.start < overlap.start {
+                    unmapped.push(Interval { start: interval.start, end: overlap.start - 1 });
+                }
+                if interval.end > overlap.end {
+                    unmapped.push(Interval { start: overlap.end + 1, end: interval.end });
+                }
+            }
+            pending = unmapped;
+        }
+
+        pieces.extend(pending.into_iter().map(|interval| (interval, 0)));
+        pieces.sort();
+        pieces
+    }
+
+    /// Every value that `convert_value` turns into a value in `targets`, sorted and merged
+    pub fn reverse_intervals(&self, targets: &[Interval]) -> Vec<Interval> {
+        let mut sources = Vec::new();
+        for (piece, offset) in self.pieces() {
+            for target in targets {
+                // Shifting back can go past the ends of an i64, but the part inside the piece can't
+                let start = (piece.start as i128).max(target.start as i128 - offset);
+                let end = (piece.end as i128).min(target.end as i128 - offset);
+                if start <= end {
+                    sources.push(Interval { start: start as i64, end: end as i64 });
+                }
+            }
+        }
+        normalize(sources)
+    }
+}
+
+/// The seed ranges on the `seeds:` line, given as pairs of a start and a length, without any empty ones
+pub fn seed_ranges(seeds: &[i64]) -> Result<Vec<Interval>, String> {
+    if seeds.len() % 2 == 1 {
+        return Err(String::from("The seeds line should have a start and a length for every range of seeds"));
+    }
+    seeds.chunks(2)
+        .filter(|pair| pair[1] > 0)
+        .map(|pair| Interval::from_length(pair[0], pair[1])
+            .ok_or_else(|| format!("The seeds line has {} seeds from {}, which goes past the largest i64", pair[1], pair[0])))
+        .collect()
+}
+
+/// Same as `convert_along` for every value in the intervals, returned sorted and merged
+pub fn convert_along(path: &[&CategoryMap], intervals: &[Interval]) -> Vec<Interval> {
+    path.iter().fold(normalize(intervals.to_vec()), |intervals, map| map.mappings.convert_intervals(&intervals))
+}
+
+/// Every value that converts to a value in `targets` along `path`, sorted and merged
+pub fn reverse_along(path: &[&CategoryMap], targets: &[Interval]) -> Vec<Interval> {
+    path.iter().rev().fold(normalize(targets.to_vec()), |intervals, map| map.mappings.reverse_intervals(&intervals))
+}
+
+/// Every value that converts to `value` along `path`, in order. A map can send several values to the same one, so
+/// there can be more than one, or none.
+pub fn reverse_convert(path: &[&CategoryMap], value: i64) -> Vec<i64> {
+    reverse_along(path, &[Interval { start: value, end: value }]).iter()
+        .flat_map(|interval| interval.start..=interval.end)
+        .collect()
+}
+
+/// The values in both lists of sorted and merged intervals
+pub fn intersect(a: &[Interval], b: &[Interval]) -> Vec<Interval> {
+    let mut both = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        let start = a[i].start.max(b[j].start);
+        let end = a[i].end.min(b[j].end);
+        if start <= end {
+            both.push(Interval { start, end });
+        }
+        if a[i].end < b[j].end {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    both
+}
+
+/// Reads `--reverse <start>-<end>`, the values of the `--to` category to find the sources of, `None` if it isn't given.
+/// Either end can be negative, like `-5--1`, since the range is split on the first `-` after the start's sign.
+pub fn reverse_from_args(args: &[String]) -> Result<Option<Interval>, String> {
+    let Some(position) = args.iter().position(|arg| arg == "--reverse") else {
+        return Ok(None);
+    };
+    let text = args.get(position + 1).ok_or(String::from("--reverse needs a range of values"))?;
+    let parse = |number: &str| number.parse::<i64>().ok();
+    let separator = text.char_indices().skip(1).find(|&(_, c)| c == '-').map(|(i, _)| i);
+    let interval = match separator {
+        Some(i) => parse(&text[..i]).zip(parse(&text[i + 1..])).map(|(start, end)| Interval { start, end }),
+        None => parse(text).map(|value| Interval { start: value, end: value }),
+    };
+    match interval {
+        Some(interval) if interval.start <= interval.end => Ok(Some(interval)),
+        _ => Err(format!("--reverse needs `<start>-<end>` with start at most end, not `{}`", text)),
+    }
+}
+
+/// The lowest value any value in the intervals converts to, without going through them one by one
+pub fn lowest(path: &[&CategoryMap], intervals: &[Interval]) -> Option<i64> {
+    convert_along(path, intervals).first().map(|interval| interval.start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Range;
+
+    /// Maps 10..=19 to 110..=119 and 15..=24 to 215..=224, which overlap
+    fn mappings() -> Mappings {
+        Mappings { mappings: vec![
+            Range { min: 10, max: 19, conversion: -100 },
+            Range { min: 15, max: 24, conversion: -200 },
+        ] }
+    }
+
+    #[test]
+    fn intervals_split_at_range_edges() {
+        let converted = mappings().convert_intervals(&[Interval { start: 5, end: 30 }]);
+        assert_eq!(converted, vec![
+            Interval { start: 5, end: 9 },
+            Interval { start: 25, end: 30 },
+            Interval { start: 110, end: 119 },
+            Interval { start: 220, end: 224 },
+        ]);
+    }
+
+    #[test]
+    fn intervals_agree_with_convert_value() {
+        let mappings = mappings();
+        for start in 0..30 {
+            for end in start..30 {
+                let mut expected: Vec<i64> = (start..=end).map(|value| mappings.convert_value(value)).collect();
+                expected.sort();
+                let converted: Vec<i64> = mappings.convert_intervals(&[Interval { start, end }]).iter()
+                    .flat_map(|interval| interval.start..=interval.end)
+                    .collect();
+                assert_eq!(converted, expected, "{}..={}", start, end);
+            }
+        }
+    }
+
+    #[test]
+    fn normalize_merges_touching_intervals() {
+        let intervals = vec![Interval { start: 8, end: 9 }, Interval { start: 1, end: 3 }, Interval { start: 4, end: 5 }, Interval { start: 2, end: 2 }];
+        assert_eq!(normalize(intervals), vec![Interval { start: 1, end: 5 }, Interval { start: 8, end: 9 }]);
+        assert_eq!(Interval::from_length(3, 0), None);
+    }
+
+    #[test]
+    fn pieces_cover_every_value() {
+        let pieces = mappings().pieces();
+        assert_eq!(pieces, vec![
+            (Interval { start: i64::MIN, end: 9 }, 0),
+            (Interval { start: 10, end: 19 }, 100),
+            (Interval { start: 20, end: 24 }, 200),
+            (Interval { start: 25, end: i64::MAX }, 0),
+        ]);
+    }
+
+    #[test]
+    fn reverse_intervals_agree_with_convert_value() {
+        let mappings = Mappings { mappings: vec![
+            Range { min: 10, max: 19, conversion: 5 },
+            Range { min: 15, max: 24, conversion: -3 },
+            Range { min: 30, max: 34, conversion: 20 },
+        ] };
+        for start in 0..40 {
+            for end in start..40 {
+                let targets = [Interval { start, end }];
+                let sources = mappings.reverse_intervals(&targets);
+                for value in -10..60 {
+                    let converted = mappings.convert_value(value);
+                    let is_source = sources.iter().any(|source| source.start <= value && value <= source.end);
+                    assert_eq!(is_source, start <= converted && converted <= end, "{} in {}..={}", value, start, end);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn example_reverse() {
+        let almanac = crate::parse(crate::tests::EXAMPLE.as_bytes()).expect("Test - parse");
+        let path = almanac.path("seed", "location").expect("Test - path");
+        let seeds = seed_ranges(&almanac.seeds).expect("Test - seeds");
+
+        // Seed 82 is the one at location 46
+        assert_eq!(reverse_convert(&path, 46), vec![82]);
+        assert_eq!(intersect(&reverse_along(&path, &[Interval { start: 0, end: 46 }]), &seeds), vec![Interval { start: 82, end: 82 }]);
+        for seed in [79, 14, 55, 13] {
+            assert!(reverse_convert(&path, crate::convert_along(&path, seed)).contains(&seed));
+        }
+    }
+
+    #[test]
+    fn intersect_works() {
+        let a = [Interval { start: 0, end: 5 }, Interval { start: 10, end: 20 }];
+        let b = [Interval { start: 3, end: 12 }, Interval { start: 15, end: 15 }, Interval { start: 19, end: 30 }];
+        assert_eq!(intersect(&a, &b), vec![
+            Interval { start: 3, end: 5 },
+            Interval { start: 10, end: 12 },
+            Interval { start: 15, end: 15 },
+            Interval { start: 19, end: 20 },
+        ]);
+    }
+
+    #[test]
+    fn reverse_from_args_works() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<String>>();
+        assert_eq!(reverse_from_args(&args(&["day5b"])), Ok(None));
+        assert_eq!(reverse_from_args(&args(&["day5b", "--reverse", "40-50"])), Ok(Some(Interval { start: 40, end: 50 })));
+        assert_eq!(reverse_from_args(&args(&["day5b", "--reverse", "46"])), Ok(Some(Interval { start: 46, end: 46 })));
+        assert!(reverse_from_args(&args(&["day5b", "--reverse", "50-40"])).is_err());
+        assert_eq!(reverse_from_args(&args(&["day5b", "--reverse", "-5-10"])), Ok(Some(Interval { start: -5, end: 10 })));
+        assert_eq!(reverse_from_args(&args(&["day5b", "--reverse", "-10--5"])), Ok(Some(Interval { start: -10, end: -5 })));
+        assert_eq!(reverse_from_args(&args(&["day5b", "--reverse", "-7"])), Ok(Some(Interval { start: -7, end: -7 })));
+        assert!(reverse_from_args(&args(&["day5b", "--reverse", "-"])).is_err());
+        assert_eq!(describe(&[Interval { start: 40, end: 50 }, Interval { start: 60, end: 60 }]), "40-50, 60");
+        assert_eq!(describe(&[]), "none");
+    }
+
+    #[test]
+    fn example_lowest_location() {
+        let almanac = crate::parse(crate::tests::EXAMPLE.as_bytes()).expect("Test - parse");
+        let seeds = seed_ranges(&almanac.seeds).expect("Test - seeds");
+        assert_eq!(seeds, vec![Interval { start: 79, end: 92 }, Interval { start: 55, end: 67 }]);
+        assert_eq!(lowest(&almanac.path("seed", "location").expect("Test - path"), &seeds), Some(46));
+        assert_eq!(lowest(&[], &seeds), Some(55));
+        assert!(seed_ranges(&[1, 2, 3]).is_err());
+        assert_eq!(seed_ranges(&[i64::MAX, 1]), Ok(vec![Interval { start: i64::MAX, end: i64::MAX }]));
+        assert_eq!(seed_ranges(&[i64::MAX, 2]),
+            Err(String::from("The seeds line has 2 seeds from 9223372036854775807, which goes past the largest i64")));
+    }
+}
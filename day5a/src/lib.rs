@@ -0,0 +1,330 @@
+//! Almanac model shared by both parts of day 5.
+//!
+//! Every `x-to-y map:` block of the almanac converts values of category `x` to category `y`, so the maps form a
+//! directed graph of categories. A value converts from one category to another by following the maps between them,
+//! which in the puzzle is the chain from `seed` to `location`. Each category has at most one map out of it, so the
+//! way between two categories is never ambiguous.
+
+pub mod intervals;
+pub mod piecewise;
+
+use std::fmt;
+
+use aoc::input;
+
+/// Values from `min` to `max`, both included, become `value - conversion`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Range {
+    pub min: i64,
+    pub max: i64,
+    pub conversion: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mappings {
+    pub mappings: Vec<Range>,
+}
+
+impl Mappings {
+    pub fn convert_value(&self, input: i64) -> i64 {
+        for range in &self.mappings {
+            if range.min <= input && input <= range.max {
+                return input - range.conversion;
+            }
+        }
+
+        // If there is no mapping, return the value
+        input
+    }
+}
+
+/// The mappings of one `from-to-to map:` block
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategoryMap {
+    pub from: String,
+    pub to: String,
+    pub mappings: Mappings,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Almanac {
+    /// The numbers on the `seeds:` line
+    pub seeds: Vec<i64>,
+    /// The maps in the order they appear in
+    pub maps: Vec<CategoryMap>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseAlmanacError {
+    MissingSeeds,
+    InvalidSeed(String),
+    InvalidHeader(String),
+    InvalidRange(String),
+    /// A range with values, or a shift, that don't fit in an i64
+    RangeTooLarge(String),
+    RangeBeforeHeader,
+    /// A second map out of a category
+    ExtraMap { from: String, to: String },
+}
+
+impl fmt::Display for ParseAlmanacError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseAlmanacError::MissingSeeds => write!(f, "expected the almanac to start with `seeds:`"),
+            ParseAlmanacError::InvalidSeed(seed) => write!(f, "`{}` is not a seed", seed),
+            ParseAlmanacError::InvalidHeader(header) => write!(f, "expected `<from>-to-<to> map:`, found `{}`", header),
+            ParseAlmanacError::InvalidRange(range) => write!(f, "expected `<destination> <source> <length>`, found `{}`", range),
+            ParseAlmanacError::RangeTooLarge(range) => write!(f, "the range `{}` goes past what an i64 can hold", range),
+            ParseAlmanacError::RangeBeforeHeader => write!(f, "expected a map header before the first range"),
+            ParseAlmanacError::ExtraMap { from, to } => write!(f, "there is already a map out of {}, so {} to {} is one too many", from, from, to),
+        }
+    }
+}
+
+/// A parse error and the 1-based line it happened on
+#[derive(Debug, PartialEq)]
+pub struct LineError {
+    pub line: usize,
+    pub error: ParseAlmanacError,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.error)
+    }
+}
+
+/// Why two categories can't be converted between
+#[derive(Debug, PartialEq)]
+pub enum PathError {
+    UnknownCategory(String),
+    /// The maps out of `from` stop at `stopped_at` before reaching `to`
+    NoPath { from: String, to: String, stopped_at: String },
+    /// The maps out of `from` go round in a loop without reaching `to`
+    Loop { from: String, to: String },
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathError::UnknownCategory(category) => write!(f, "No map mentions the category `{}`", category),
+            PathError::NoPath { from, to, stopped_at } => write!(f, "No maps lead from {} to {}: missing a map out of {}", from, to, stopped_at),
+            PathError::Loop { from, to } => write!(f, "No maps lead from {} to {}: the maps out of {} go round in a loop", from, to, from),
+        }
+    }
+}
+
+/// Parses the header of a map, `seed-to-soil map:` into `("seed", "soil")`
+fn parse_header(line: &[u8]) -> Option<(String, String)> {
+    let header = std::str::from_utf8(line).ok()?.trim_end().strip_suffix(" map:")?;
+    let (from, to) = header.split_once("-to-")?;
+    let is_name = |name: &str| !name.is_empty() && !name.contains(char::is_whitespace);
+    (is_name(from) && is_name(to)).then(|| (from.to_string(), to.to_string()))
+}
+
+/// Parses `<destination> <source> <length>` into the range of source values it maps, `Ok(None)` if it maps none
+fn parse_range(line: &[u8]) -> Result<Option<Range>, ParseAlmanacError> {
+    let invalid = || ParseAlmanacError::InvalidRange(String::from_utf8_lossy(line).to_string());
+    let numbers: Vec<i64> = line.split(|b| b.is_ascii_whitespace())
+        .filter(|number| !number.is_empty())
+        .map(input::parse_i64)
+        .collect::<Option<Vec<i64>>>()
+        .ok_or_else(invalid)?;
+    let &[destination, source, length] = numbers.as_slice() else {
+        return Err(invalid());
+    };
+    if length < 0 {
+        return Err(invalid());
+    }
+    if length == 0 {
+        return Ok(None);
+    }
+
+    // With both ends of both ranges in an i64, converting any value of the range can't overflow
+    let max = source.checked_add(length - 1);
+    let conversion = source.checked_sub(destination);
+    match (max, conversion, destination.checked_add(length - 1)) {
+        (Some(max), Some(conversion), Some(_)) => Ok(Some(Range { min: source, max, conversion })),
+        _ => Err(ParseAlmanacError::RangeTooLarge(String::from_utf8_lossy(line).to_string())),
+    }
+}
+
+/// Parses the seeds and every map of the almanac, whatever the categories and in any order
+pub fn parse(input: &[u8]) -> Result<Almanac, LineError> {
+    let lossy = |bytes: &[u8]| String::from_utf8_lossy(bytes).to_string();
+    let mut lines = input::lines(input).enumerate().map(|(i, line)| (i + 1, line));
+
+    let (_, seeds_line) = lines.next().ok_or(LineError { line: 1, error: ParseAlmanacError::MissingSeeds })?;
+    let seeds = seeds_line.strip_prefix(b"seeds:")
+        .ok_or(LineError { line: 1, error: ParseAlmanacError::MissingSeeds })?;
+    let seeds = seeds.split(|b| b.is_ascii_whitespace())
+        .filter(|seed| !seed.is_empty())
+        .map(|seed| input::parse_i64(seed).ok_or_else(|| LineError { line: 1, error: ParseAlmanacError::InvalidSeed(lossy(seed)) }))
+        .collect::<Result<Vec<i64>, LineError>>()?;
+
+    let mut maps: Vec<CategoryMap> = Vec::new();
+    for (line, bytes) in lines {
+        if bytes.trim_ascii().is_empty() {
+            continue;
+        }
+
+        if bytes.contains(&b':') {
+            let (from, to) = parse_header(bytes)
+                .ok_or_else(|| LineError { line, error: ParseAlmanacError::InvalidHeader(lossy(bytes)) })?;
+            if maps.iter().any(|map| map.from == from) {
+                return Err(LineError { line, error: ParseAlmanacError::ExtraMap { from, to } });
+            }
+            maps.push(CategoryMap { from, to, mappings: Mappings { mappings: Vec::new() } });
+            continue;
+        }
+
+        let range = parse_range(bytes).map_err(|error| LineError { line, error })?;
+        let map = maps.last_mut().ok_or(LineError { line, error: ParseAlmanacError::RangeBeforeHeader })?;
+        map.mappings.mappings.extend(range);
+    }
+
+    Ok(Almanac { seeds, maps })
+}
+
+impl Almanac {
+    /// The map out of `category`, if there is one
+    pub fn map_from(&self, category: &str) -> Option<&CategoryMap> {
+        self.maps.iter().find(|map| map.from == category)
+    }
+
+    /// Every category any map mentions, in the order they first appear
+    pub fn categories(&self) -> Vec<&str> {
+        let mut categories: Vec<&str> = Vec::new();
+        for map in &self.maps {
+            for category in [map.from.as_str(), map.to.as_str()] {
+                if !categories.contains(&category) {
+                    categories.push(category);
+                }
+            }
+        }
+        categories
+    }
+
+    /// The maps to go through, in order, to convert values of `from` to `to`. Empty if they're the same category.
+    pub fn path(&self, from: &str, to: &str) -> Result<Vec<&CategoryMap>, PathError> {
+        let categories = self.categories();
+        for category in [from, to] {
+            if !categories.contains(&category) {
+                return Err(PathError::UnknownCategory(category.to_string()));
+            }
+        }
+
+        let mut path = Vec::new();
+        let mut category = from;
+        while category != to {
+            let Some(map) = self.map_from(category) else {
+                return Err(PathError::NoPath { from: from.to_string(), to: to.to_string(), stopped_at: category.to_string() });
+            };
+            // A path can't take more maps than there are without going round in a loop
+            if path.len() == self.maps.len() {
+                return Err(PathError::Loop { from: from.to_string(), to: to.to_string() });
+            }
+            path.push(map);
+            category = &map.to;
+        }
+        Ok(path)
+    }
+}
+
+/// Converts a value through every map of a path
+pub fn convert_along(path: &[&CategoryMap], value: i64) -> i64 {
+    path.iter().fold(value, |value, map| map.mappings.convert_value(value))
+}
+
+/// Reads `--from <category>` and `--to <category>`, `seed` and `location` by default
+pub fn categories_from_args(args: &[String]) -> Result<(String, String), String> {
+    let get_option = |name: &str, default: &str| match args.iter().position(|arg| arg == name) {
+        Some(position) => args.get(position + 1).cloned().ok_or(format!("{} needs a category", name)),
+        None => Ok(default.to_string()),
+    };
+
+    Ok((get_option("--from", "seed")?, get_option("--to", "location")?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(crate) const EXAMPLE: &str = "seeds: 79 14 55 13\n\nseed-to-soil map:\n50 98 2\n52 50 48\n\nsoil-to-fertilizer map:\n0 15 37\n\
+        37 52 2\n39 0 15\n\nfertilizer-to-water map:\n49 53 8\n0 11 42\n42 0 7\n57 7 4\n\nwater-to-light map:\n88 18 7\n\
+        18 25 70\n\nlight-to-temperature map:\n45 77 23\n81 45 19\n68 64 13\n\ntemperature-to-humidity map:\n0 69 1\n\
+        1 0 69\n\nhumidity-to-location map:\n60 56 37\n56 93 4\n";
+
+    #[test]
+    fn example_locations() {
+        let almanac = parse(EXAMPLE.as_bytes()).expect("Test - parse");
+        assert_eq!(almanac.seeds, vec![79, 14, 55, 13]);
+        assert_eq!(almanac.categories(), vec!["seed", "soil", "fertilizer", "water", "light", "temperature", "humidity", "location"]);
+
+        let path = almanac.path("seed", "location").expect("Test - path");
+        assert_eq!(path.len(), 7);
+        let locations: Vec<i64> = almanac.seeds.iter().map(|&seed| convert_along(&path, seed)).collect();
+        assert_eq!(locations, vec![82, 43, 86, 35]);
+    }
+
+    #[test]
+    fn converts_between_any_connected_categories() {
+        let almanac = parse(EXAMPLE.as_bytes()).expect("Test - parse");
+        // Seed 79 is soil 81, fertilizer 81, water 81, light 74, temperature 78 and humidity 78
+        let path = almanac.path("soil", "humidity").expect("Test - path");
+        assert_eq!(path.iter().map(|map| map.to.as_str()).collect::<Vec<&str>>(), vec!["fertilizer", "water", "light", "temperature", "humidity"]);
+        assert_eq!(convert_along(&path, 81), 78);
+        assert_eq!(almanac.path("water", "water").expect("Test - path"), Vec::<&CategoryMap>::new());
+
+        assert_eq!(almanac.path("location", "seed"), Err(PathError::NoPath {
+            from: String::from("location"),
+            to: String::from("seed"),
+            stopped_at: String::from("location"),
+        }));
+        assert_eq!(almanac.path("seed", "color"), Err(PathError::UnknownCategory(String::from("color"))));
+    }
+
+    #[test]
+    fn maps_can_come_in_any_order() {
+        let almanac = parse(b"seeds: 1 5\n\nb-to-c map:\n100 0 10\n\na-to-b map:\n3 1 2\n").expect("Test - parse");
+        let path = almanac.path("a", "c").expect("Test - path");
+        assert_eq!(almanac.seeds.iter().map(|&seed| convert_along(&path, seed)).collect::<Vec<i64>>(), vec![103, 105]);
+    }
+
+    #[test]
+    fn missing_maps_are_errors() {
+        let almanac = parse(b"seeds: 1\n\na-to-b map:\n\nc-to-d map:\n").expect("Test - parse");
+        assert_eq!(almanac.path("a", "d").map_err(|error| error.to_string()),
+            Err(String::from("No maps lead from a to d: missing a map out of b")));
+
+        let looped = parse(b"seeds: 1\n\na-to-b map:\n\nb-to-a map:\n\nc-to-d map:\n").expect("Test - parse");
+        assert_eq!(looped.path("a", "d"), Err(PathError::Loop { from: String::from("a"), to: String::from("d") }));
+    }
+
+    #[test]
+    fn parse_reports_bad_almanacs() {
+        let error = |input: &[u8]| parse(input).expect_err("Test - parse").to_string();
+        assert_eq!(error(b"soil: 1\n"), "Line 1: expected the almanac to start with `seeds:`");
+        assert_eq!(error(b"seeds: 1 x\n"), "Line 1: `x` is not a seed");
+        assert_eq!(error(b"seeds: 1\n\n1 2 3\n"), "Line 3: expected a map header before the first range");
+        assert_eq!(error(b"seeds: 1\n\nseed to soil:\n"), "Line 3: expected `<from>-to-<to> map:`, found `seed to soil:`");
+        assert_eq!(error(b"seeds: 1\n\na-to-b map:\n1 2\n"), "Line 4: expected `<destination> <source> <length>`, found `1 2`");
+        assert_eq!(error(b"seeds: 1\n\na-to-b map:\n1 2 -3\n"), "Line 4: expected `<destination> <source> <length>`, found `1 2 -3`");
+        assert_eq!(error(b"seeds: 1\n\na-to-b map:\n9223372036854775807 0 2\n"),
+            "Line 4: the range `9223372036854775807 0 2` goes past what an i64 can hold");
+        assert_eq!(error(b"seeds: 1\n\na-to-b map:\n9223372036854775807 -2 1\n"),
+            "Line 4: the range `9223372036854775807 -2 1` goes past what an i64 can hold");
+        assert_eq!(parse(b"seeds: 1\n\na-to-b map:\n1 2 0\n").map(|almanac| almanac.maps[0].mappings.mappings.len()), Ok(0));
+        assert_eq!(error(b"seeds: 1\n\na-to-b map:\n1 2 3\n\na-to-c map:\n"),
+            "Line 6: there is already a map out of a, so a to c is one too many");
+    }
+
+    #[test]
+    fn categories_from_args_works() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<String>>();
+        assert_eq!(categories_from_args(&args(&["day5a"])), Ok((String::from("seed"), String::from("location"))));
+        assert_eq!(categories_from_args(&args(&["day5a", "--to", "humidity", "--from", "soil"])),
+            Ok((String::from("soil"), String::from("humidity"))));
+        assert_eq!(categories_from_args(&args(&["day5a", "--from"])), Err(String::from("--from needs a category")));
+    }
+}
@@ -1,76 +1,69 @@
-use std::fs;
+use std::{env, fs};
+
+use aoc::input;
+use day5::{intervals::{self, Interval}, piecewise::PiecewiseMap, CategoryMap, Mappings, Range};
 
 // TODO: Write a faster algorithm for this problem.
 fn main() {
+    let args: Vec<String> = env::args().collect();
+
     let contents = fs::read_to_string("input.txt")
         .expect("Should have been able to read input.txt");
 
-    // Initialization
-    let (seeds, almanac) = parse_input(&contents);
-
-    let lowest_location = find_lowest_location(seeds, almanac);
+    if args.iter().any(|arg| arg == "--compare-parsers") {
+        input::compare_parsers(contents.trim_end(), 100, parse_input, parse_input_bytes);
+        return;
+    }
 
-    println!("Lowest location: {}", lowest_location);
-}
+    // Initialization
+    let almanac = aoc::unwrap_or_exit(day5::parse(contents.as_bytes()));
+    let (from, to) = aoc::unwrap_or_exit(day5::categories_from_args(&args));
+    let path = aoc::unwrap_or_exit(almanac.path(&from, &to));
+
+    let seeds = Seeds { seeds: almanac.seeds.clone() };
+
+    if let Some(targets) = aoc::unwrap_or_exit(intervals::reverse_from_args(&args)) {
+        let sources = intervals::reverse_along(&path, &[targets]);
+        println!("Sources in {} of {} {}: {}", from, to, targets, intervals::describe(&sources));
+        let seeds: Vec<Interval> = seeds.seeds.iter()
+            .filter(|&&seed| sources.iter().any(|source| source.start <= seed && seed <= source.end))
+            .map(|&seed| Interval { start: seed, end: seed })
+            .collect();
+        println!("Seeds in the almanac: {}", intervals::describe(&seeds));
+        return;
+    }
 
-fn find_lowest_location(seeds: Seeds, almanac: Almanac) -> u32 {
-
-    let mut lowest_location = u32::MAX;
-    for seed in seeds.seeds {
-        let soil_value = almanac.seed_to_soil.convert_value(seed);
-        let fertilizer_value = almanac.soil_to_fertilizer.convert_value(soil_value);
-        let water_value = almanac.fertilizer_to_water.convert_value(fertilizer_value);
-        let light_value = almanac.water_to_light.convert_value(water_value);
-        let temperature_value = almanac.light_to_temperature.convert_value(light_value);
-        let humidity_value = almanac.temperature_to_humidity.convert_value(temperature_value);
-        let location_value = almanac.humidity_to_location.convert_value(humidity_value);
-
-        if (location_value as u32) < lowest_location {
-            lowest_location = location_value as u32;
-        }
+    if args.iter().any(|arg| arg == "--flatten") {
+        print!("{}", PiecewiseMap::compose(&path));
+        return;
     }
 
-    return lowest_location;
-}
+    if args.iter().any(|arg| arg == "--compare-solvers") {
+        // The map is flattened once up front, so only the lookups are timed
+        let flattened = PiecewiseMap::compose(&path);
+        input::compare_implementations(&(seeds, path, flattened), 100,
+            ("chain", |(seeds, path, _)| find_lowest(seeds, path)),
+            ("flattened", |(seeds, _, flattened)| seeds.seeds.iter().map(|&seed| flattened.convert(seed)).min()));
+        return;
+    }
 
-struct Seeds {
-    seeds: Vec<i64>,
-}
+    let lowest = aoc::unwrap_or_exit(find_lowest(&seeds, &path).ok_or("There are no seeds"));
 
-struct Range {
-    min: i64,
-    max: i64,
-    conversion: i64
+    println!("Lowest {}: {}", to, lowest);
 }
 
-struct Mappings {
-    mappings: Vec<Range>
+/// The lowest value the seeds convert to along `path`, treating them as values of the category it starts from
+fn find_lowest(seeds: &Seeds, path: &[&CategoryMap]) -> Option<i64> {
+    seeds.seeds.iter().map(|&seed| day5::convert_along(path, seed)).min()
 }
 
-impl Mappings {
-    fn convert_value(&self, input: i64) -> i64 {
-        for range in &self.mappings {
-            if range.min <= input && input <= range.max {
-                return input - range.conversion;
-            }
-        }
-
-        // If there is no mapping, return the value
-        return input
-    }
-}
-
-struct Almanac {
-    seed_to_soil: Mappings,
-    soil_to_fertilizer: Mappings,
-    fertilizer_to_water: Mappings,
-    water_to_light: Mappings,
-    light_to_temperature: Mappings,
-    temperature_to_humidity: Mappings,
-    humidity_to_location: Mappings,
+#[derive(PartialEq, Debug)]
+struct Seeds {
+    seeds: Vec<i64>,
 }
 
-fn parse_input(input: &str) -> (Seeds, Almanac) {
+/// The original parser, kept as a reference for `day5::parse`
+fn parse_input(input: &str) -> (Seeds, Vec<CategoryMap>) {
     let mut iter = input.split("\n\n");
     let seeds = parse_seeds(iter.next().expect("Should be able to get seeds"));
     let almanac = parse_almanac(iter);
@@ -87,31 +80,33 @@ fn parse_seeds(input: &str) -> Seeds {
         seeds.push(seed.parse::<i64>().expect("Should have been able to parse seed number"));
     }
 
-    Seeds { seeds: seeds }
+    Seeds { seeds }
+}
+
+/// Same as `parse_input`, through the library's parser
+fn parse_input_bytes(input: &[u8]) -> (Seeds, Vec<CategoryMap>) {
+    let almanac = aoc::unwrap_or_exit(day5::parse(input));
+    (Seeds { seeds: almanac.seeds }, almanac.maps)
 }
 
-fn parse_almanac<'a, T>(mut iter: T) -> Almanac
+fn parse_almanac<'a, T>(iter: T) -> Vec<CategoryMap>
 where 
     T: Iterator<Item = &'a str>
 {
-    Almanac {
-        seed_to_soil: parse_mappings(iter.next()),
-        soil_to_fertilizer: parse_mappings(iter.next()),
-        fertilizer_to_water: parse_mappings(iter.next()),
-        water_to_light: parse_mappings(iter.next()),
-        light_to_temperature: parse_mappings(iter.next()),
-        temperature_to_humidity: parse_mappings(iter.next()),
-        humidity_to_location: parse_mappings(iter.next()),
-    }
+    iter.map(|map| parse_mappings(Some(map))).collect()
 }
 
-fn parse_mappings(map: Option<&str>) -> Mappings
+fn parse_mappings(map: Option<&str>) -> CategoryMap
 {
     let mut mappings : Vec<Range> = Vec::new();
 
     let map_str = map.expect("Should have been able to get map");
     let mut iter = map_str.split('\n');
-    iter.next(); // skip map name
+    let (from, to) = iter.next()
+        .expect("Should have a map name")
+        .trim_end_matches(" map:")
+        .split_once("-to-")
+        .expect("Map name should be <from>-to-<to>");
     for line in iter {
         let mut range_info = line.split(' ');
 
@@ -128,8 +123,10 @@ fn parse_mappings(map: Option<&str>) -> Mappings
         )
     }
 
-    Mappings {
-        mappings: mappings
+    CategoryMap {
+        from: from.to_string(),
+        to: to.to_string(),
+        mappings: Mappings { mappings },
     }
 }
 
@@ -156,12 +153,44 @@ mod tests {
         assert_eq!(seeds.seeds, vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
     }
 
+    #[test]
+    fn test_parse_input_bytes() {
+        let input = "seeds: 79 14 55 13\n\nseed-to-soil map:\n50 98 2\n52 50 48\n\nsoil-to-fertilizer map:\n0 15 37\n\n\
+            fertilizer-to-water map:\n49 53 8\n\nwater-to-light map:\n88 18 7\n\nlight-to-temperature map:\n45 77 23\n\n\
+            temperature-to-humidity map:\n0 69 1\n\nhumidity-to-location map:\n60 56 37\n56 93 4";
+
+        let (seeds, almanac) = parse_input_bytes(input.as_bytes());
+        let (expected_seeds, expected_almanac) = parse_input(input);
+
+        assert_eq!(seeds, expected_seeds);
+        assert_eq!(almanac, expected_almanac);
+        assert_eq!(almanac.len(), 7);
+        assert_eq!(almanac[6].from, "humidity");
+        assert_eq!(almanac[6].mappings.mappings.len(), 2);
+    }
+
+    #[test]
+    fn lowest_between_any_categories() {
+        let input = "seeds: 79 14 55 13\n\nseed-to-soil map:\n50 98 2\n52 50 48\n\nsoil-to-fertilizer map:\n0 15 37\n\
+            37 52 2\n39 0 15\n\nfertilizer-to-water map:\n49 53 8\n0 11 42\n42 0 7\n57 7 4\n\nwater-to-light map:\n88 18 7\n\
+            18 25 70\n\nlight-to-temperature map:\n45 77 23\n81 45 19\n68 64 13\n\ntemperature-to-humidity map:\n0 69 1\n\
+            1 0 69\n\nhumidity-to-location map:\n60 56 37\n56 93 4\n";
+        let almanac = day5::parse(input.as_bytes()).expect("Test - parse");
+        let seeds = Seeds { seeds: almanac.seeds.clone() };
+
+        assert_eq!(find_lowest(&seeds, &almanac.path("seed", "location").expect("Test - path")), Some(35));
+        // Read as soil, 13 is fertilizer 52, water 41, light 34, temperature 34 and humidity 35
+        assert_eq!(find_lowest(&seeds, &almanac.path("soil", "humidity").expect("Test - path")), Some(35));
+        assert_eq!(find_lowest(&Seeds { seeds: Vec::new() }, &[]), None);
+    }
+
     #[test]
     fn test_parse_mappings() {
         let input = Some("seed-to-soil map:\n0 2 2");
         let parse_mappings = parse_mappings(input);
 
-        let get = parse_mappings.mappings.get(0).expect("Test - come on");
+        assert_eq!((parse_mappings.from.as_str(), parse_mappings.to.as_str()), ("seed", "soil"));
+        let get = parse_mappings.mappings.mappings.first().expect("Test - come on");
         assert_eq!(get.min, 2);
         assert_eq!(get.max, 3);
         assert_eq!(get.conversion, 2);
@@ -0,0 +1,216 @@
+//! A chain of maps flattened into one.
+//!
+//! Each map adds an offset that depends on which piece of the number line a value is in (see
+//! [`Mappings::pieces`]). Following a piece of one map into the pieces of the next gives pieces that go through both
+//! with one offset, so a whole path composes into a single sorted list of `(start, end, offset)` pieces. A lookup is
+//! then a binary search instead of a scan of every range of every map, and the list can be written out as a table to
+//! inspect or diff the effective conversion of two almanacs.
+
+use std::fmt;
+
+use crate::{intervals::Interval, CategoryMap, Mappings};
+
+/// Values from `start` to `end`, both included, become `value + offset`, which is still an i64
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Piece {
+    pub start: i64,
+    pub end: i64,
+    pub offset: i128,
+}
+
+impl Piece {
+    /// What a value of the piece becomes
+    fn shift(&self, value: i64) -> i64 {
+        i64::try_from(value as i128 + self.offset).expect("Should have pieces that stay within an i64")
+    }
+}
+
+/// Sorted pieces that cover every i64 without overlapping, with no two pieces next to each other sharing an offset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PiecewiseMap {
+    pieces: Vec<Piece>,
+}
+
+impl PiecewiseMap {
+    /// The map that keeps every value
+    pub fn identity() -> PiecewiseMap {
+        PiecewiseMap { pieces: vec![Piece { start: i64::MIN, end: i64::MAX, offset: 0 }] }
+    }
+
+    pub fn from_mappings(mappings: &Mappings) -> PiecewiseMap {
+        let pieces = mappings.pieces().into_iter()
+            .map(|(interval, offset)| Piece { start: interval.start, end: interval.end, offset })
+            .collect();
+        PiecewiseMap { pieces: merge(pieces) }
+    }
+
+    /// Every map of the path in one
+    pub fn compose(path: &[&CategoryMap]) -> PiecewiseMap {
+        path.iter().fold(PiecewiseMap::identity(), |map, next| map.then(&PiecewiseMap::from_mappings(&next.mappings)))
+    }
+
+    /// The map that converts a value with `self` and then with `next`
+    pub fn then(&self, next: &PiecewiseMap) -> PiecewiseMap {
+        let mut pieces = Vec::new();
+        for piece in &self.pieces {
+            // Where the piece's values go, which can be past the ends of an i64 if they're shifted
+            let image_start = piece.start as i128 + piece.offset;
+            let image_end = piece.end as i128 + piece.offset;
+
+            let first = next.pieces.partition_point(|next| (next.end as i128) < image_start);
+            for next in next.pieces[first..].iter().take_while(|next| next.start as i128 <= image_end) {
+                let start = image_start.max(next.start as i128) - piece.offset;
+                let end = image_end.min(next.end as i128) - piece.offset;
+                pieces.push(Piece { start: start as i64, end: end as i64, offset: piece.offset + next.offset });
+            }
+        }
+        PiecewiseMap { pieces: merge(pieces) }
+    }
+
+    /// Same as going through every map of the path, with a binary search for the value's piece
+    pub fn convert(&self, value: i64) -> i64 {
+        let piece = &self.pieces[self.pieces.partition_point(|piece| piece.end < value)];
+        piece.shift(value)
+    }
+
+    pub fn pieces(&self) -> &[Piece] {
+        &self.pieces
+    }
+
+    /// Same as `intervals::lowest`: the lowest value any value in the sorted and merged intervals converts to
+    pub fn lowest(&self, intervals: &[Interval]) -> Option<i64> {
+        let both = |interval: &Interval, piece: &Piece| interval.start.max(piece.start) <= interval.end.min(piece.end);
+        intervals.iter()
+            .flat_map(|interval| {
+                let first = self.pieces.partition_point(|piece| piece.end < interval.start);
+                self.pieces[first..].iter()
+                    .take_while(|piece| piece.start <= interval.end)
+                    .filter(move |piece| both(interval, piece))
+                    .map(move |piece| piece.shift(interval.start.max(piece.start)))
+            })
+            .min()
+    }
+
+    /// Reads a table written by `Display` back
+    pub fn parse_table(table: &str) -> Result<PiecewiseMap, String> {
+        let mut pieces = Vec::new();
+        for (i, line) in table.lines().enumerate() {
+            if i == 0 && line.split_whitespace().eq(["start", "end", "offset"]) || line.trim().is_empty() {
+                continue;
+            }
+            let numbers: Option<Vec<i128>> = line.split_whitespace().map(|number| number.parse().ok()).collect();
+            let Some((Ok(start), Ok(end), offset)) = numbers.as_deref()
+                .and_then(|numbers| match *numbers {
+                    [start, end, offset] => Some((i64::try_from(start), i64::try_from(end), offset)),
+                    _ => None,
+                }) else {
+                return Err(format!("Line {}: expected `<start> <end> <offset>`, found `{}`", i + 1, line));
+            };
+            let expected_start = pieces.last().map_or(Some(i64::MIN), |piece: &Piece| piece.end.checked_add(1));
+            if Some(start) != expected_start || end < start {
+                return Err(format!("Line {}: the pieces should cover every value in order", i + 1));
+            }
+            let fits = |value: i64| (value as i128).checked_add(offset).is_some_and(|value| i64::try_from(value).is_ok());
+            if !fits(start) || !fits(end) {
+                return Err(format!("Line {}: the piece moves values past the ends of an i64", i + 1));
+            }
+            pieces.push(Piece { start, end, offset });
+        }
+        if pieces.last().map(|piece| piece.end) != Some(i64::MAX) {
+            return Err(String::from("The pieces should cover every value up to the largest i64"));
+        }
+        Ok(PiecewiseMap { pieces: merge(pieces) })
+    }
+}
+
+/// Sorts the pieces and joins the ones next to each other with the same offset
+fn merge(mut pieces: Vec<Piece>) -> Vec<Piece> {
+    pieces.sort_by_key(|piece| piece.start);
+    let mut merged: Vec<Piece> = Vec::with_capacity(pieces.len());
+    for piece in pieces {
+        match merged.last_mut() {
+            Some(last) if last.offset == piece.offset && last.end.checked_add(1) == Some(piece.start) => last.end = piece.end,
+            _ => merged.push(piece),
+        }
+    }
+    merged
+}
+
+/// A table with a `start end offset` row per piece, under a header
+impl fmt::Display for PiecewiseMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:>20} {:>20} {:>20}", "start", "end", "offset")?;
+        for piece in &self.pieces {
+            writeln!(f, "{:>20} {:>20} {:>20}", piece.start, piece.end, piece.offset)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{convert_along, intervals, tests::EXAMPLE};
+
+    #[test]
+    fn example_composes_into_one_map() {
+        let almanac = crate::parse(EXAMPLE.as_bytes()).expect("Test - parse");
+        let path = almanac.path("seed", "location").expect("Test - path");
+        let map = PiecewiseMap::compose(&path);
+
+        for value in -20..200 {
+            assert_eq!(map.convert(value), convert_along(&path, value), "{}", value);
+        }
+        assert_eq!(map.convert(i64::MIN), i64::MIN);
+        assert_eq!(map.convert(i64::MAX), i64::MAX);
+
+        let seeds = intervals::seed_ranges(&almanac.seeds).expect("Test - seeds");
+        assert_eq!(map.lowest(&intervals::normalize(seeds)), Some(46));
+    }
+
+    #[test]
+    fn pieces_are_merged() {
+        let almanac = crate::parse(b"seeds: 1\n\na-to-b map:\n20 10 5\n25 15 5\n\nb-to-c map:\n10 20 10\n").expect("Test - parse");
+        let map = PiecewiseMap::compose(&almanac.path("a", "c").expect("Test - path"));
+        // 10..=19 go to 20..=29 and then back to 10..=19, so they keep their number like everything else
+        assert_eq!(map, PiecewiseMap { pieces: vec![
+            Piece { start: i64::MIN, end: 19, offset: 0 },
+            Piece { start: 20, end: 29, offset: -10 },
+            Piece { start: 30, end: i64::MAX, offset: 0 },
+        ] });
+        assert_eq!(PiecewiseMap::compose(&[]), PiecewiseMap::identity());
+    }
+
+    #[test]
+    fn extreme_ranges_compose() {
+        // The smallest i64 goes to 0 and then to the largest, which is more than an i64 offset can hold
+        let almanac = crate::parse(b"seeds: 1\n\na-to-b map:\n0 -9223372036854775808 1\n\nb-to-c map:\n9223372036854775807 0 1\n")
+            .expect("Test - parse");
+        let path = almanac.path("a", "c").expect("Test - path");
+        let map = PiecewiseMap::compose(&path);
+
+        for value in [i64::MIN, i64::MIN + 1, -1, 0, 1, i64::MAX] {
+            assert_eq!(map.convert(value), convert_along(&path, value), "{}", value);
+        }
+        assert_eq!(map.lowest(&[Interval { start: i64::MIN, end: i64::MIN }]), Some(i64::MAX));
+        assert_eq!(map.pieces()[0], Piece { start: i64::MIN, end: i64::MIN, offset: u64::MAX as i128 });
+        assert_eq!(PiecewiseMap::parse_table(&map.to_string()), Ok(map));
+    }
+
+    #[test]
+    fn table_round_trips() {
+        let almanac = crate::parse(EXAMPLE.as_bytes()).expect("Test - parse");
+        let map = PiecewiseMap::compose(&almanac.path("seed", "location").expect("Test - path"));
+        let table = map.to_string();
+        assert!(table.starts_with("               start                  end               offset\n"));
+        assert_eq!(PiecewiseMap::parse_table(&table), Ok(map));
+
+        assert_eq!(PiecewiseMap::parse_table("start end offset\n-9223372036854775808 5 0\n7 9223372036854775807 1\n"),
+            Err(String::from("Line 3: the pieces should cover every value in order")));
+        assert_eq!(PiecewiseMap::parse_table("-9223372036854775808 5 0\n"),
+            Err(String::from("The pieces should cover every value up to the largest i64")));
+        assert_eq!(PiecewiseMap::parse_table("-9223372036854775808 9223372036854775807 1\n"),
+            Err(String::from("Line 1: the piece moves values past the ends of an i64")));
+        assert_eq!(PiecewiseMap::parse_table("1 2\n"), Err(String::from("Line 1: expected `<start> <end> <offset>`, found `1 2`")));
+    }
+}
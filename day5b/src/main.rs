@@ -1,75 +1,71 @@
-use std::fs;
+use std::{env, fs};
+
+use aoc::{alloc::measure, input};
+use day5::{intervals::{self, Interval}, piecewise::PiecewiseMap, CategoryMap, Mappings, Range};
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
+
     let contents = fs::read_to_string("input.txt")
         .expect("Should have been able to read input.txt");
 
-    // Initialization
-    let (seeds, almanac) = parse_input(&contents);
-
-    let lowest_location = find_lowest_location(seeds, almanac);
-
-    println!("Lowest location: {}", lowest_location);
-}
-
-fn find_lowest_location(seeds: Seeds, almanac: Almanac) -> u32 {
+    if args.iter().any(|arg| arg == "--compare-parsers") {
+        input::compare_parsers(contents.trim_end(), 100, parse_input, parse_input_bytes);
+        return;
+    }
 
-    let mut lowest_location = u32::MAX;
-    for seed in seeds.seeds {
-        let soil_value = almanac.seed_to_soil.convert_value(seed);
-        let fertilizer_value = almanac.soil_to_fertilizer.convert_value(soil_value);
-        let water_value = almanac.fertilizer_to_water.convert_value(fertilizer_value);
-        let light_value = almanac.water_to_light.convert_value(water_value);
-        let temperature_value = almanac.light_to_temperature.convert_value(light_value);
-        let humidity_value = almanac.temperature_to_humidity.convert_value(temperature_value);
-        let location_value = almanac.humidity_to_location.convert_value(humidity_value);
+    // Initialization
+    let almanac = aoc::unwrap_or_exit(measure("parse", || day5::parse(contents.as_bytes())));
+    let seeds = aoc::unwrap_or_exit(intervals::seed_ranges(&almanac.seeds));
+    let (from, to) = aoc::unwrap_or_exit(day5::categories_from_args(&args));
+    let path = aoc::unwrap_or_exit(almanac.path(&from, &to));
+
+    if let Some(targets) = aoc::unwrap_or_exit(intervals::reverse_from_args(&args)) {
+        let sources = intervals::reverse_along(&path, &[targets]);
+        println!("Sources in {} of {} {}: {}", from, to, targets, intervals::describe(&sources));
+        println!("Seeds in the almanac: {}", intervals::describe(&intervals::intersect(&sources, &intervals::normalize(seeds))));
+        return;
+    }
 
-        if (location_value as u32) < lowest_location {
-            lowest_location = location_value as u32;
-        }
+    if args.iter().any(|arg| arg == "--flatten") {
+        print!("{}", PiecewiseMap::compose(&path));
+        return;
     }
 
-    return lowest_location;
-}
+    if args.iter().any(|arg| arg == "--compare-solvers") {
+        input::compare_implementations(&(seeds, path), 1,
+            ("brute force", |(seeds, path)| find_lowest(&expand_seeds(seeds), path)),
+            ("intervals", |(seeds, path)| intervals::lowest(path, seeds)));
+        return;
+    }
 
-struct Seeds {
-    seeds: Vec<i64>,
-}
+    let lowest = if args.iter().any(|arg| arg == "--brute-force") {
+        measure("part", || find_lowest(&expand_seeds(&seeds), &path))
+    } else {
+        measure("part", || intervals::lowest(&path, &seeds))
+    };
 
-struct Range {
-    min: i64,
-    max: i64,
-    conversion: i64
+    println!("Lowest {}: {}", to, aoc::unwrap_or_exit(lowest.ok_or("There are no seeds")));
 }
 
-struct Mappings {
-    mappings: Vec<Range>
+/// The original solver, kept as a reference for `intervals::lowest`: every seed goes through every map on its own,
+/// which is billions of seeds on a real input
+fn find_lowest(seeds: &Seeds, path: &[&CategoryMap]) -> Option<i64> {
+    seeds.seeds.iter().map(|&seed| day5::convert_along(path, seed)).min()
 }
 
-impl Mappings {
-    fn convert_value(&self, input: i64) -> i64 {
-        for range in &self.mappings {
-            if range.min <= input && input <= range.max {
-                return input - range.conversion;
-            }
-        }
-
-        // If there is no mapping, return the value
-        return input
-    }
+#[derive(PartialEq, Debug)]
+struct Seeds {
+    seeds: Vec<i64>,
 }
 
-struct Almanac {
-    seed_to_soil: Mappings,
-    soil_to_fertilizer: Mappings,
-    fertilizer_to_water: Mappings,
-    water_to_light: Mappings,
-    light_to_temperature: Mappings,
-    temperature_to_humidity: Mappings,
-    humidity_to_location: Mappings,
+/// Every seed in the intervals, one by one
+fn expand_seeds(intervals: &[Interval]) -> Seeds {
+    Seeds { seeds: intervals.iter().flat_map(|interval| interval.start..=interval.end).collect() }
 }
 
-fn parse_input(input: &str) -> (Seeds, Almanac) {
+/// The original parser, kept as a reference for `day5::parse`
+fn parse_input(input: &str) -> (Seeds, Vec<CategoryMap>) {
     let mut iter = input.split("\n\n");
     let seeds = parse_seeds(iter.next().expect("Should be able to get seeds"));
     let almanac = parse_almanac(iter);
@@ -90,31 +86,34 @@ fn parse_seeds(input: &str) -> Seeds {
         }
     }
 
-    Seeds { seeds: seeds }
+    Seeds { seeds }
+}
+
+/// Same as `parse_input`, through the library's parser
+fn parse_input_bytes(input: &[u8]) -> (Seeds, Vec<CategoryMap>) {
+    let almanac = aoc::unwrap_or_exit(day5::parse(input));
+    let seeds = aoc::unwrap_or_exit(intervals::seed_ranges(&almanac.seeds));
+    (expand_seeds(&seeds), almanac.maps)
 }
 
-fn parse_almanac<'a, T>(mut iter: T) -> Almanac
+fn parse_almanac<'a, T>(iter: T) -> Vec<CategoryMap>
 where 
     T: Iterator<Item = &'a str>
 {
-    Almanac {
-        seed_to_soil: parse_mappings(iter.next()),
-        soil_to_fertilizer: parse_mappings(iter.next()),
-        fertilizer_to_water: parse_mappings(iter.next()),
-        water_to_light: parse_mappings(iter.next()),
-        light_to_temperature: parse_mappings(iter.next()),
-        temperature_to_humidity: parse_mappings(iter.next()),
-        humidity_to_location: parse_mappings(iter.next()),
-    }
+    iter.map(|map| parse_mappings(Some(map))).collect()
 }
 
-fn parse_mappings(map: Option<&str>) -> Mappings
+fn parse_mappings(map: Option<&str>) -> CategoryMap
 {
     let mut mappings : Vec<Range> = Vec::new();
 
     let map_str = map.expect("Should have been able to get map");
     let mut iter = map_str.split('\n');
-    iter.next(); // skip map name
+    let (from, to) = iter.next()
+        .expect("Should have a map name")
+        .trim_end_matches(" map:")
+        .split_once("-to-")
+        .expect("Map name should be <from>-to-<to>");
     for line in iter {
         let mut range_info = line.split(' ');
 
@@ -131,8 +130,10 @@ fn parse_mappings(map: Option<&str>) -> Mappings
         )
     }
 
-    Mappings {
-        mappings: mappings
+    CategoryMap {
+        from: from.to_string(),
+        to: to.to_string(),
+        mappings: Mappings { mappings },
     }
 }
 
@@ -146,6 +147,7 @@ fn parse_int_or_panic(int : Option<&str>) -> i64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aoc::testing::{check_generated, Rng};
 
     #[test]
     fn test_parse_almanac() {
@@ -159,12 +161,89 @@ mod tests {
         assert_eq!(seeds.seeds, vec![1, 2]);
     }
 
+    #[test]
+    fn test_parse_input_bytes() {
+        let input = "seeds: 79 14 55 13\n\nseed-to-soil map:\n50 98 2\n52 50 48\n\nsoil-to-fertilizer map:\n0 15 37\n\n\
+            fertilizer-to-water map:\n49 53 8\n\nwater-to-light map:\n88 18 7\n\nlight-to-temperature map:\n45 77 23\n\n\
+            temperature-to-humidity map:\n0 69 1\n\nhumidity-to-location map:\n60 56 37\n56 93 4";
+
+        let (seeds, almanac) = parse_input_bytes(input.as_bytes());
+        let (expected_seeds, expected_almanac) = parse_input(input);
+
+        assert_eq!(seeds, expected_seeds);
+        assert_eq!(almanac, expected_almanac);
+        assert_eq!(almanac.len(), 7);
+        assert_eq!(almanac[6].mappings.mappings.len(), 2);
+    }
+
+    const EXAMPLE: &str = "seeds: 79 14 55 13\n\nseed-to-soil map:\n50 98 2\n52 50 48\n\nsoil-to-fertilizer map:\n0 15 37\n\
+        37 52 2\n39 0 15\n\nfertilizer-to-water map:\n49 53 8\n0 11 42\n42 0 7\n57 7 4\n\nwater-to-light map:\n88 18 7\n\
+        18 25 70\n\nlight-to-temperature map:\n45 77 23\n81 45 19\n68 64 13\n\ntemperature-to-humidity map:\n0 69 1\n\
+        1 0 69\n\nhumidity-to-location map:\n60 56 37\n56 93 4\n";
+
+    #[test]
+    fn example_lowest_location() {
+        let almanac = day5::parse(EXAMPLE.as_bytes()).expect("Test - parse");
+        let seeds = intervals::seed_ranges(&almanac.seeds).expect("Test - seeds");
+        let path = almanac.path("seed", "location").expect("Test - path");
+        assert_eq!(intervals::lowest(&path, &seeds), Some(46));
+        assert_eq!(find_lowest(&expand_seeds(&seeds), &path), Some(46));
+    }
+
+    #[test]
+    fn intervals_match_brute_force_on_generated_almanacs() {
+        // Seed ranges and maps small enough to go through seed by seed, and a range of locations to look up
+        let names = ["seed", "soil", "fertilizer", "water", "light", "temperature", "humidity", "location"];
+        let generate = |rng: &mut Rng, _| {
+            let mut input = String::from("seeds:");
+            for _ in 0..1 + rng.below(4) {
+                input.push_str(&format!(" {} {}", rng.below(200), rng.below(40)));
+            }
+            input.push('\n');
+            for pair in names.windows(2) {
+                input.push_str(&format!("\n{}-to-{} map:\n", pair[0], pair[1]));
+                for _ in 0..1 + rng.below(5) {
+                    input.push_str(&format!("{} {} {}\n", rng.below(250), rng.below(250), 1 + rng.below(60)));
+                }
+            }
+            (input, Interval { start: rng.below(300) as i64, end: 300 + rng.below(100) as i64 })
+        };
+
+        check_generated(5, 30, generate, |(input, target)| {
+            let almanac = day5::parse(input.as_bytes()).expect("Test - parse");
+            let seeds = intervals::seed_ranges(&almanac.seeds).expect("Test - seeds");
+            for (from, to) in [("seed", "location"), ("soil", "humidity"), ("light", "light")] {
+                let path = almanac.path(from, to).expect("Test - path");
+                assert_eq!(intervals::lowest(&path, &seeds), find_lowest(&expand_seeds(&seeds), &path), "{} to {}", from, to);
+                let flattened = PiecewiseMap::compose(&path);
+                assert_eq!(flattened.lowest(&intervals::normalize(seeds.clone())), intervals::lowest(&path, &seeds));
+                for value in -50..500 {
+                    assert_eq!(flattened.convert(value), day5::convert_along(&path, value), "{}", value);
+                }
+            }
+
+            // Every value the inverse finds converts into the target, and every value that does is found
+            let path = almanac.path("seed", "location").expect("Test - path");
+            let sources = intervals::reverse_along(&path, std::slice::from_ref(target));
+            for value in -50..500 {
+                let location = day5::convert_along(&path, value);
+                let is_source = sources.iter().any(|source| source.start <= value && value <= source.end);
+                assert_eq!(is_source, target.start <= location && location <= target.end, "{}", value);
+            }
+            for location in 0..20 {
+                let expected: Vec<i64> = (-50..500).filter(|&value| day5::convert_along(&path, value) == location).collect();
+                let found: Vec<i64> = intervals::reverse_convert(&path, location).into_iter().filter(|value| (-50..500).contains(value)).collect();
+                assert_eq!(found, expected, "{}", location);
+            }
+        });
+    }
+
     #[test]
     fn test_parse_mappings() {
         let input = Some("seed-to-soil map:\n0 2 2");
         let parse_mappings = parse_mappings(input);
 
-        let get = parse_mappings.mappings.get(0).expect("Test - come on");
+        let get = parse_mappings.mappings.mappings.first().expect("Test - come on");
         assert_eq!(get.min, 2);
         assert_eq!(get.max, 3);
         assert_eq!(get.conversion, 2);
@@ -1,5 +1,8 @@
-use std::{fs, mem::Discriminant};
+use std::{env, fs};
 
+use aoc::input;
+
+#[derive(PartialEq, Debug)]
 struct RaceRecord {
     time: u32,
     distance: u32
@@ -9,7 +12,12 @@ fn main() {
     let contents = fs::read_to_string("input.txt")
         .expect("Should be able to read from file input.txt");
 
-    let race_records = parse_race_records(contents);
+    if env::args().any(|arg| arg == "--compare-parsers") {
+        input::compare_parsers(&contents, 100, |contents| parse_race_records(contents.to_string()), parse_race_records_bytes);
+        return;
+    }
+
+    let race_records = parse_race_records_bytes(contents.as_bytes());
 
     print_race_records(&race_records);
 
@@ -76,8 +84,47 @@ fn parse_race_records(contents: String) -> Vec<RaceRecord> {
     race_records
 }
 
-fn print_race_records(race_records: &Vec<RaceRecord>) {
+/// Same as `parse_race_records`, reading the numbers straight out of the input bytes
+fn parse_race_records_bytes(contents: &[u8]) -> Vec<RaceRecord> {
+    let mut lines = input::lines(contents);
+    let times: Vec<i64> = input::numbers(lines.next().expect("Should get first line")).collect::<Result<_, _>>().expect("Should parse");
+    let distances: Vec<i64> = input::numbers(lines.next().expect("Should get second line")).collect::<Result<_, _>>().expect("Should parse");
+
+    if times.len() != distances.len() {
+        panic!("There are a different number of times and distances in the input");
+    }
+
+    times.iter()
+        .zip(distances.iter())
+        .map(|(&time, &distance)| RaceRecord {
+            time: u32::try_from(time).expect("Should parse"),
+            distance: u32::try_from(distance).expect("Should parse"),
+        })
+        .collect()
+}
+
+fn print_race_records(race_records: &[RaceRecord]) {
     for (i, race_record) in race_records.iter().enumerate() {
         println!("Race {}: time: {}, distance: {}", i, race_record.time, race_record.distance);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_race_records_bytes() {
+        let contents = String::from("Time:      7  15   30\nDistance:  9  40  200\n");
+        let race_records = parse_race_records_bytes(contents.as_bytes());
+
+        assert_eq!(race_records, parse_race_records(contents.clone()));
+        assert_eq!(race_records[2], RaceRecord { time: 30, distance: 200 });
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_parse_race_records_bytes_rejects_large_numbers() {
+        parse_race_records_bytes(b"Time: 4294967296\nDistance: 9\n");
+    }
+}
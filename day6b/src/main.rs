@@ -1,5 +1,8 @@
-use std::fs;
+use std::{env, fs};
 
+use aoc::input;
+
+#[derive(PartialEq, Debug)]
 struct RaceRecord {
     time: u64,
     distance: u64
@@ -9,7 +12,12 @@ fn main() {
     let contents = fs::read_to_string("input.txt")
         .expect("Should be able to read from file input.txt");
 
-    let race_records = parse_race_records(contents);
+    if env::args().any(|arg| arg == "--compare-parsers") {
+        input::compare_parsers(&contents, 100, |contents| parse_race_records(contents.to_string()), parse_race_records_bytes);
+        return;
+    }
+
+    let race_records = parse_race_records_bytes(contents.as_bytes());
 
     print_race_records(&race_records);
 
@@ -73,20 +81,62 @@ fn parse_race_records(contents: String) -> Vec<RaceRecord> {
     }
 
 
-    let mut race_records: Vec<RaceRecord> = Vec::new();
-
-    race_records.push(
+    vec![
         RaceRecord {
             time: time_string.parse::<u64>().expect("Should parse"),
             distance: distance_string.parse::<u64>().expect("Should parse"),
         }
-    );
+    ]
+}
 
-    race_records
+/// Same as `parse_race_records`, reading the digits straight out of the input bytes
+fn parse_race_records_bytes(contents: &[u8]) -> Vec<RaceRecord> {
+    let mut lines = input::lines(contents);
+    let times = lines.next().expect("Should get first line");
+    let distances = lines.next().expect("Should get second line");
+
+    if input::numbers(times).count() != input::numbers(distances).count() {
+        panic!("There are a different number of times and distances in the input");
+    }
+
+    vec![
+        RaceRecord {
+            time: concatenate_digits(times),
+            distance: concatenate_digits(distances),
+        }
+    ]
 }
 
-fn print_race_records(race_records: &Vec<RaceRecord>) {
+/// Reads every digit on the line as one number, ignoring the spaces between them
+fn concatenate_digits(line: &[u8]) -> u64 {
+    line.iter()
+        .filter(|b| b.is_ascii_digit())
+        .try_fold(0u64, |value, b| value.checked_mul(10)?.checked_add((b - b'0') as u64))
+        .expect("Should parse")
+}
+
+fn print_race_records(race_records: &[RaceRecord]) {
     for (i, race_record) in race_records.iter().enumerate() {
         println!("Race {}: time: {}, distance: {}", i, race_record.time, race_record.distance);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_race_records_bytes() {
+        let contents = String::from("Time:      7  15   30\nDistance:  9  40  200\n");
+        let race_records = parse_race_records_bytes(contents.as_bytes());
+
+        assert_eq!(race_records, parse_race_records(contents.clone()));
+        assert_eq!(race_records, vec![RaceRecord { time: 71530, distance: 940200 }]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_parse_race_records_bytes_rejects_large_numbers() {
+        parse_race_records_bytes(b"Time: 1844674407 3709551616\nDistance: 9 1\n");
+    }
+}
@@ -1,5 +1,8 @@
-use std::{fs, cmp::Ordering, collections::HashMap};
+use std::{env, fs, cmp::Ordering, collections::HashMap, str};
 
+use aoc::input;
+
+#[derive(PartialEq, Debug)]
 struct Hand<'a> {
     hand: &'a str,
     bid: u64,
@@ -8,8 +11,14 @@ struct Hand<'a> {
 fn main() {
     let contents = fs::read_to_string("input.txt")
         .expect("Should have been able to read input.txt");
+    aoc::unwrap_or_exit(input::check_ascii(contents.as_bytes()));
+
+    if env::args().any(|arg| arg == "--compare-parsers") {
+        input::compare_parsers(&contents, 100, parse_content, parse_content_bytes);
+        return;
+    }
 
-    let mut hands: Vec<Hand> = parse_content(&contents);
+    let mut hands: Vec<Hand> = parse_content_bytes(contents.as_bytes());
 
     sort_hands(&mut hands);
 
@@ -143,7 +152,7 @@ fn get_hand_strength(hand: &str) -> u32 {
     0 // High card
 }
 
-fn parse_content<'a>(contents: &'a String) -> Vec<Hand<'a>> {
+fn parse_content(contents: &str) -> Vec<Hand<'_>> {
     let mut hands: Vec<Hand> = Vec::new();
     for line in contents.lines() {
         let mut split = line.split_whitespace();
@@ -152,8 +161,27 @@ fn parse_content<'a>(contents: &'a String) -> Vec<Hand<'a>> {
 
         hands.push(
             Hand {
-                hand: hand,
-                bid: bid,
+                hand,
+                bid,
+            }
+        )
+    }
+
+    hands
+}
+
+/// Same as `parse_content`, borrowing each hand straight out of the input bytes
+fn parse_content_bytes(contents: &[u8]) -> Vec<Hand<'_>> {
+    let mut hands: Vec<Hand> = Vec::new();
+    for line in input::lines(contents) {
+        let mut split = input::words(line);
+        let hand = split.next().expect("Hand should exist");
+        let bid = split.next().expect("Bid should exist");
+
+        hands.push(
+            Hand {
+                hand: str::from_utf8(hand).expect("Hand should be text"),
+                bid: input::parse_u64(bid).expect("Bid should be a number"),
             }
         )
     }
@@ -165,6 +193,24 @@ fn parse_content<'a>(contents: &'a String) -> Vec<Hand<'a>> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_content_bytes() {
+        let contents = String::from("32T3K 765\nT55J5 684\nKK677 28\n");
+        let hands = parse_content_bytes(contents.as_bytes());
+
+        assert_eq!(hands, parse_content(&contents));
+        assert_eq!(hands[1], Hand { hand: "T55J5", bid: 684 });
+    }
+
+    #[test]
+    fn test_parse_content_bytes_splits_on_any_whitespace() {
+        let contents = String::from(" 32T3K  765\nT55J5\t684 \r\n");
+        let hands = parse_content_bytes(contents.as_bytes());
+
+        assert_eq!(hands, parse_content(&contents));
+        assert_eq!(hands[0], Hand { hand: "32T3K", bid: 765 });
+    }
+
     #[test]
     fn test_sort_hands_should_work() {
         let mut hands = vec![
@@ -1,5 +1,8 @@
-use std::{fs, collections::HashMap};
+use std::{env, fs, collections::HashMap};
 
+use aoc::input;
+
+#[derive(PartialEq, Debug)]
 struct Node {
     id: String,
     left: String,
@@ -10,16 +13,21 @@ fn main() {
     let contents = fs::read_to_string("input.txt")
         .expect("Should be able to read the input");
 
-    let mut iter = contents.lines();
+    if env::args().any(|arg| arg == "--compare-parsers") {
+        input::compare_parsers(&contents, 100, parse_nodes, parse_nodes_bytes);
+        return;
+    }
+
+    let mut iter = input::lines(contents.as_bytes());
     let instructions = iter.next().expect("Instructions should exist");
     iter.next(); // skip empty line
 
     // parse map
     let map: HashMap::<String, Node> = HashMap::new();
-    let (curr_node, map) = parse_map(map, iter);
+    let (_curr_node, map) = parse_map_bytes(map, iter);
 
-    let vec: Vec<char> = instructions.chars().collect();
-    let steps = get_total_steps(String::from("AAA"), vec, map, &"ZZZ");
+    let vec: Vec<char> = instructions.iter().map(|&b| b as char).collect();
+    let steps = get_total_steps(String::from("AAA"), vec, map, "ZZZ");
 
     println!("Total steps: {}", steps);
 }
@@ -86,6 +94,61 @@ fn get_node(node_str: &str) -> Node {
     }
 }
 
+/// Same as `parse_map`, for lines of the input bytes
+fn parse_map_bytes<'a, T> (mut map: HashMap<String, Node>, mut iter: T) -> (String, HashMap<String, Node>)
+where T: Iterator<Item = &'a [u8]>
+{
+    let root_node_str = iter.next().expect("Root node");
+
+    let node = get_node_bytes(root_node_str);
+
+    let curr_node = node.id.clone();
+
+    map.insert(node.id.clone(), node);
+
+    for line in iter {
+        let node = get_node_bytes(line);
+        map.insert(node.id.clone(), node);
+    }
+
+    (curr_node, map)
+}
+
+/// Same as `get_node`, copying each id out of the line once instead of trimming and replacing into new `String`s
+fn get_node_bytes(node_str: &[u8]) -> Node {
+    let (id, edges) = input::split_once(node_str, b'=').expect("Left and Right node values should exist");
+    let (left, right) = input::split_once(edges, b',').expect("Right node should exist");
+
+    let left = left.trim_ascii();
+    let right = right.trim_ascii();
+
+    Node {
+        id: bytes_to_string(id.trim_ascii()),
+        left: bytes_to_string(left.strip_prefix(b"(").unwrap_or(left)),
+        right: bytes_to_string(right.strip_suffix(b")").unwrap_or(right)),
+    }
+}
+
+fn bytes_to_string(bytes: &[u8]) -> String {
+    String::from_utf8(bytes.to_vec()).expect("Node ids should be text")
+}
+
+/// Parses every node of the input, used to compare the two parsers
+fn parse_nodes(contents: &str) -> HashMap<String, Node> {
+    let mut iter = contents.lines();
+    iter.next(); // skip instructions
+    iter.next(); // skip empty line
+    parse_map(HashMap::new(), iter).1
+}
+
+/// Same as `parse_nodes`, for the input bytes
+fn parse_nodes_bytes(contents: &[u8]) -> HashMap<String, Node> {
+    let mut iter = input::lines(contents);
+    iter.next(); // skip instructions
+    iter.next(); // skip empty line
+    parse_map_bytes(HashMap::new(), iter).1
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -107,6 +170,16 @@ mod tests {
         assert_maps_are_equal(&expected_map, &map);
     }
 
+    #[test]
+    fn parse_map_bytes_works() {
+        let input = String::from("AAA = (BBB, CCC)\nBBB = (AAA, AAA)\nCCC = (DDD, EEE)\nDDD = (CCC, AAA)\nEEE = (AAA, AAA)\n");
+        let (root, map) = parse_map_bytes(HashMap::new(), input::lines(input.as_bytes()));
+        let (expected_root, expected_map) = parse_map(HashMap::new(), input.lines());
+
+        assert_eq!(root, expected_root);
+        assert_maps_are_equal(&expected_map, &map);
+    }
+
     #[test]
     fn traverse_map_from_instructions_works() {
         let mut map: HashMap<String, Node> = HashMap::new();
@@ -116,7 +189,7 @@ mod tests {
         add_to_map(&mut map, "DDD", "CCC", "AAA");
         add_to_map(&mut map, "EEE", "AAA", "AAA");
 
-        let total_steps = get_total_steps(String::from("AAA"), vec!['L','R','R','R'], map, &"EEE");
+        let total_steps = get_total_steps(String::from("AAA"), vec!['L','R','R','R'], map, "EEE");
 
         assert_eq!(4, total_steps);
     }
@@ -130,7 +203,7 @@ mod tests {
         add_to_map(&mut map, "DDD", "CCC", "BBB");
         add_to_map(&mut map, "EEE", "AAA", "AAA");
 
-        let total_steps = get_total_steps(String::from("AAA"), vec!['L','R'], map, &"EEE");
+        let total_steps = get_total_steps(String::from("AAA"), vec!['L','R'], map, "EEE");
 
         assert_eq!(5, total_steps);
     }
@@ -144,7 +217,7 @@ mod tests {
         add_to_map(&mut map, "DDD", "CCC", "BBB");
         add_to_map(&mut map, "EEE", "AAA", "AAA");
 
-        let total_steps = get_total_steps(String::from("AAA"), vec!['L','R','L'], map, &"EEE");
+        let total_steps = get_total_steps(String::from("AAA"), vec!['L','R','L'], map, "EEE");
 
         assert_eq!(5, total_steps);
     }
@@ -160,7 +233,7 @@ mod tests {
     }
 
     fn assert_maps_are_equal(expected_map: &HashMap<String, Node>, actual_map: &HashMap<String, Node>) {
-        if (expected_map.len() != actual_map.len())
+        if expected_map.len() != actual_map.len()
         {
             panic!("There is a different number of keys in each map");
         }
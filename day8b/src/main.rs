@@ -1,42 +1,41 @@
-use petgraph::{graph::{NodeIndex, Graph}, visit::EdgeRef};
-use std::{fs::{self, File}, collections::HashMap, io::Write};
+use aoc::input;
+use petgraph::{graph::{NodeIndex, Graph}, unionfind::UnionFind, visit::{Dfs, EdgeRef}};
+use std::{env, fs::{self, File}, collections::HashMap, f64::consts::PI, io::Write};
+
+/// A node's id and the ids of its left and right neighbors
+type Node = (String, String, String);
 
 fn main() {
     let contents = fs::read_to_string("input.txt")
         .expect("Something went wrong reading the file"); 
 
-    let mut lines = contents.lines();
-    let instructions = lines.next().expect("Instructions should exist");
-    lines.next(); // skip blank line
+    if env::args().any(|arg| arg == "--compare-parsers") {
+        input::compare_parsers(&contents, 100, parse_input, parse_input_bytes);
+        return;
+    }
+
+    let (instructions, nodes) = parse_input_bytes(contents.as_bytes());
+    let instructions = instructions.as_str();
 
     let mut g: Graph<String, String> = Graph::new();
     
     let mut map: HashMap<String, NodeIndex> = HashMap::new();
 
-    for line in lines {
-        let mut split = line.split("=");
-        let node_val = split.next().expect("Should have a left side").trim().to_string();
-        let edges_to = split.next().expect("Should have a right side").trim().to_string();
-        let mut edges_split = edges_to.split(", ");
-
+    for (node_val, left_val, right_val) in nodes {
         let a = get_or_add_node(&mut g, &mut map, node_val); 
-
-        let left_val = edges_split.next().expect("Should have a value").trim().replace('(', "");
-        let right_val = edges_split.next().expect("Should have a value").trim().replace(')', "");
-
         let left = get_or_add_node(&mut g, &mut map, left_val); 
         let right = get_or_add_node(&mut g, &mut map, right_val); 
         g.add_edge(a, left, String::from("L"));
         g.add_edge(a, right, String::from("R"));
-
     }
 
     write_to_file_graph(&g).expect("Should have been able to write to file");
+    write_report_svg(&g).expect("Should have been able to write report.svg");
 
-    let starting_points = get_starting_points(&g, &|x| (g.node_weight(x).expect("Should have a weight").contains('A')));
+    let starting_points = get_starting_points(&g, &|x| g.node_weight(x).expect("Should have a weight").contains('A'));
 
     let answers : Vec<u64> = starting_points.iter()
-        .map(|x: &NodeIndex| get_cycle_length(&g, *x, &instructions, 100000) as u64)
+        .map(|x: &NodeIndex| get_cycle_length(&g, *x, instructions, 100000) as u64)
         .collect();
 
     println!("Answer: {}", calculate_lcm(answers));
@@ -44,8 +43,8 @@ fn main() {
 
 fn calculate_lcm(values: Vec<u64>) -> u64 {
     let mut lcm = values[0];
-    for i in 1..values.len() {
-        lcm = (lcm * values[i]) / gcd(lcm, values[i]);
+    for value in values.iter().skip(1) {
+        lcm = (lcm * value) / gcd(lcm, *value);
     }
     lcm
 }
@@ -59,7 +58,56 @@ fn gcd(a: u64, b: u64) -> u64 {
         b = a % b;
         a = temp;
     }
-    return a;
+    a
+}
+
+/// The original parser, kept as a reference for `parse_input_bytes`: the instructions and every node in input order
+fn parse_input(contents: &str) -> (String, Vec<Node>) {
+    let mut lines = contents.lines();
+    let instructions = lines.next().expect("Instructions should exist").to_string();
+    lines.next(); // skip blank line
+
+    let mut nodes = Vec::new();
+    for line in lines {
+        let mut split = line.split("=");
+        let node_val = split.next().expect("Should have a left side").trim().to_string();
+        let edges_to = split.next().expect("Should have a right side").trim().to_string();
+        let mut edges_split = edges_to.split(", ");
+
+        let left_val = edges_split.next().expect("Should have a value").trim().replace('(', "");
+        let right_val = edges_split.next().expect("Should have a value").trim().replace(')', "");
+
+        nodes.push((node_val, left_val, right_val));
+    }
+
+    (instructions, nodes)
+}
+
+/// Same as `parse_input`, copying each id out of the line once instead of trimming and replacing into new `String`s
+fn parse_input_bytes(contents: &[u8]) -> (String, Vec<Node>) {
+    let mut lines = input::lines(contents);
+    let instructions = bytes_to_string(lines.next().expect("Instructions should exist"));
+    lines.next(); // skip blank line
+
+    let nodes = lines
+        .map(|line| {
+            let (node_val, edges_to) = input::split_once(line, b'=').expect("Should have a right side");
+            let (left_val, right_val) = input::split_once(edges_to, b',').expect("Should have a value");
+            let (left_val, right_val) = (left_val.trim_ascii(), right_val.trim_ascii());
+
+            (
+                bytes_to_string(node_val.trim_ascii()),
+                bytes_to_string(left_val.strip_prefix(b"(").unwrap_or(left_val)),
+                bytes_to_string(right_val.strip_suffix(b")").unwrap_or(right_val)),
+            )
+        })
+        .collect();
+
+    (instructions, nodes)
+}
+
+fn bytes_to_string(bytes: &[u8]) -> String {
+    String::from_utf8(bytes.to_vec()).expect("Node ids should be text")
 }
 
 fn get_or_add_node(g: &mut Graph<String, String>, map: &mut HashMap<String, NodeIndex>, val: String) -> NodeIndex {
@@ -121,7 +169,7 @@ fn get_cycle_length(g: &Graph<String, String>, starting_point: NodeIndex, instru
         panic!("Not all differences are equal - cannot use LCM algorithm")
     }
 
-    return differences[0];
+    differences[0]
 }
 
 fn write_to_file_graph(g: &Graph<String, String>) -> std::io::Result<()> {
@@ -134,4 +182,97 @@ fn write_to_file_graph(g: &Graph<String, String>) -> std::io::Result<()> {
     writeln!(file, "}}")?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+const COMPONENT_SIZE: f64 = 300.0;
+
+/// Draws every connected component on its own circle, for `aoc report`.
+/// Nodes are ordered by a walk from the starting node so each cycle reads as a ring.
+/// Starting nodes are red and ending nodes green, as in visualization.py
+fn write_report_svg(g: &Graph<String, String>) -> std::io::Result<()> {
+    let mut union_find: UnionFind<usize> = UnionFind::new(g.node_count());
+    for edge in g.edge_references() {
+        union_find.union(edge.source().index(), edge.target().index());
+    }
+
+    let mut component_ids: HashMap<usize, usize> = HashMap::new();
+    let mut components: Vec<Vec<NodeIndex>> = Vec::new();
+    for node in g.node_indices() {
+        let root = union_find.find(node.index());
+        let id = *component_ids.entry(root).or_insert_with(|| {
+            components.push(Vec::new());
+            components.len() - 1
+        });
+        components[id].push(node);
+    }
+
+    let mut positions: Vec<(f64, f64)> = vec![(0.0, 0.0); g.node_count()];
+    let columns = (components.len() as f64).sqrt().ceil() as usize;
+    for (k, component) in components.iter().enumerate() {
+        let ordered = order_component(g, component);
+        let center_x = ((k % columns) as f64 + 0.5) * COMPONENT_SIZE;
+        let center_y = ((k / columns) as f64 + 0.5) * COMPONENT_SIZE;
+        let radius = COMPONENT_SIZE * 0.4;
+
+        for (i, node) in ordered.iter().enumerate() {
+            let angle = 2.0 * PI * i as f64 / ordered.len() as f64;
+            positions[node.index()] = (center_x + radius * angle.cos(), center_y + radius * angle.sin());
+        }
+    }
+
+    let rows = components.len().div_ceil(columns.max(1));
+    let mut file = File::create("report.svg")?;
+    writeln!(file, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">",
+        columns as f64 * COMPONENT_SIZE, rows as f64 * COMPONENT_SIZE)?;
+    writeln!(file, "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>")?;
+
+    for edge in g.edge_references() {
+        let (x1, y1) = positions[edge.source().index()];
+        let (x2, y2) = positions[edge.target().index()];
+        writeln!(file, "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#999\" stroke-width=\"0.5\"/>", x1, y1, x2, y2)?;
+    }
+
+    for node in g.node_indices() {
+        let name = g.node_weight(node).expect("Should have a weight");
+        let color = if name.contains('A') { "red" } else if name.contains('Z') { "green" } else { "blue" };
+        let (x, y) = positions[node.index()];
+        writeln!(file, "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"2.5\" fill=\"{}\"><title>{}</title></circle>", x, y, color, name)?;
+    }
+
+    writeln!(file, "</svg>")?;
+    Ok(())
+}
+
+/// Orders a component by a depth first walk from its starting node, followed by anything the walk did not reach
+fn order_component(g: &Graph<String, String>, component: &[NodeIndex]) -> Vec<NodeIndex> {
+    let start = component.iter()
+        .find(|node| g.node_weight(**node).expect("Should have a weight").contains('A'))
+        .unwrap_or(&component[0]);
+
+    let mut ordered: Vec<NodeIndex> = Vec::new();
+    let mut dfs = Dfs::new(g, *start);
+    while let Some(node) = dfs.next(g) {
+        ordered.push(node);
+    }
+
+    for node in component {
+        if !ordered.contains(node) {
+            ordered.push(*node);
+        }
+    }
+
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_input_bytes() {
+        let contents = "LR\n\n11A = (11B, XXX)\n11B = (XXX, 11Z)\n11Z = (11B, XXX)\nXXX = (XXX, XXX)\n";
+
+        assert_eq!(parse_input_bytes(contents.as_bytes()), parse_input(contents));
+        assert_eq!(parse_input_bytes(contents.as_bytes()).1[0], (String::from("11A"), String::from("11B"), String::from("XXX")));
+    }
+}
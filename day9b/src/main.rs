@@ -1,18 +1,75 @@
-use std::fs;
+use std::{env, fs};
+
+use aoc::{breakdown::{self, Breakdown, Value}, input::{self, StreamArgs}};
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    aoc::unwrap_or_exit(breakdown::check_not_streaming(&args));
+    if let Some(stream) = aoc::unwrap_or_exit(StreamArgs::from_args(&args, "input.txt")) {
+        let mut line_number = 0;
+        let sum = stream.sum_lines(|line| {
+            line_number += 1;
+            get_next_element(aoc::unwrap_or_exit(parse_sequence(line_number, line))).expect("Should have an answer")
+        })
+            .expect("Should have been able to read the input");
+        println!("Sum: {}", sum);
+        return;
+    }
+
     let contents = fs::read_to_string("input.txt")
         .expect("Something went wrong reading the file");
 
+    if args.iter().any(|arg| arg == "--compare-parsers") {
+        input::compare_parsers(&contents, 100, parse_sequences, |contents| aoc::unwrap_or_exit(parse_sequences_bytes(contents)));
+        return;
+    }
+
+    if let Some(path) = breakdown::path_from_args(&args) {
+        aoc::unwrap_or_exit(get_breakdown(contents.as_bytes())).write(path)
+            .expect("Should have been able to write the breakdown");
+        println!("Breakdown written to {}", path);
+    }
+
     let mut sum = 0;
-    for line in contents.lines() {
-        let els: Vec<i64> = line.split(' ').map(|x| x.parse::<i64>().expect("Should be able to parse into u64")).collect();
+    for els in aoc::unwrap_or_exit(parse_sequences_bytes(contents.as_bytes())) {
         let answer = get_next_element(els);
         sum += answer.expect("Should have an answer");
     }
     println!("Sum: {}", sum);
 }
 
+fn parse_sequences(contents: &str) -> Vec<Vec<i64>> {
+    contents.lines()
+        .map(|line| line.split(' ').map(|x| x.parse::<i64>().expect("Should be able to parse into u64")).collect())
+        .collect()
+}
+
+/// Same as `parse_sequences`, reading the numbers straight out of the input bytes. Where that panics on a token
+/// that isn't a number, this returns an error with its line.
+fn parse_sequences_bytes(contents: &[u8]) -> Result<Vec<Vec<i64>>, String> {
+    input::lines(contents)
+        .enumerate()
+        .map(|(i, line)| parse_sequence(i + 1, line))
+        .collect()
+}
+
+/// `line_number` is 1-based, for the error
+fn parse_sequence(line_number: usize, line: &[u8]) -> Result<Vec<i64>, String> {
+    input::parse_numbers(line).map_err(|error| format!("Line {}: {}", line_number, error))
+}
+
+fn get_breakdown(contents: &[u8]) -> Result<Breakdown, String> {
+    let mut breakdown = Breakdown::new(&["length", "first"]);
+    for (i, line) in input::lines(contents).enumerate() {
+        let sequence = parse_sequence(i + 1, line)?;
+        let first = sequence.first().copied().unwrap_or_default();
+        breakdown.add(i + 1, line, get_next_element(sequence.clone()).expect("Should have an answer"),
+            vec![Value::number(sequence.len()), Value::number(first)]);
+    }
+    Ok(breakdown)
+}
+
 fn get_next_element(els: Vec<i64>) -> Option<i64> {
     let mut vecs: Vec<Vec<i64>> = Vec::new();
     vecs.push(els);
@@ -32,3 +89,28 @@ fn get_next_element(els: Vec<i64>) -> Option<i64> {
 
     Some(new_first)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sequences_bytes() {
+        let contents = "0 3 6 9 12 15\n1 3 6 10 15 21\n-4 -2 0 2\n";
+        let sequences = parse_sequences_bytes(contents.as_bytes()).expect("Test - parse");
+
+        assert_eq!(sequences, parse_sequences(contents));
+        assert_eq!(sequences[2], vec![-4, -2, 0, 2]);
+    }
+
+    #[test]
+    fn test_parse_sequences_bytes_rejects_what_parse_sequences_panics_on() {
+        for (contents, error) in [
+            ("1 2 x 3\n", "Line 1: `x` is not a number that fits in an i64"),
+            ("1 2\n1 99999999999999999999 3 4\n", "Line 2: `99999999999999999999` is not a number that fits in an i64"),
+        ] {
+            assert!(std::panic::catch_unwind(|| parse_sequences(contents)).is_err(), "{:?}", contents);
+            assert_eq!(parse_sequences_bytes(contents.as_bytes()), Err(String::from(error)));
+        }
+    }
+}